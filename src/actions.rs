@@ -1,4 +1,26 @@
 use std::collections::HashSet;
+use std::process::Command;
+
+/// Probes `$PATH` for each interpreter `run_script` supports (`which` on
+/// Unix, `where` on Windows), so `ActionContext.has_interpreters` only
+/// advertises languages actually installed on this machine instead of
+/// assuming a fixed dev-container image.
+pub fn detect_interpreters() -> HashSet<&'static str> {
+    const CANDIDATES: &[(&str, &str)] = &[
+        ("python", "python3"),
+        ("node", "node"),
+        ("bash", "bash"),
+        ("pwsh", "pwsh"),
+        ("ruby", "ruby"),
+        ("php", "php"),
+    ];
+    let probe = if cfg!(target_os = "windows") { "where" } else { "which" };
+    CANDIDATES
+        .iter()
+        .filter(|(_, bin)| Command::new(probe).arg(bin).output().map(|o| o.status.success()).unwrap_or(false))
+        .map(|(lang, _)| *lang)
+        .collect()
+}
 
 pub struct ActionContext<'a> {
     pub user_input: &'a str,
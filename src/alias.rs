@@ -0,0 +1,88 @@
+//! Persistent `alias` layer, a lighter-weight companion to `create_tool`:
+//! short names that expand to a fuller tool invocation (e.g. `gs` expanding
+//! to `shell: git status`), stored alongside discovered tool scripts under
+//! `./prime/` so they survive restarts the same way `tool_*.sh` scripts do.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Filename (under the workspace's `./prime/` directory) that persists the
+/// alias table across sessions.
+const ALIASES_FILE: &str = "aliases.txt";
+
+/// Maps a short alias name to the full tool invocation text it expands to,
+/// persisted as one `name=expansion` line per alias.
+#[derive(Debug, Clone, Default)]
+pub struct AliasMap {
+    aliases: BTreeMap<String, String>,
+}
+
+impl AliasMap {
+    fn path(workspace: &Path) -> PathBuf {
+        workspace.join("prime").join(ALIASES_FILE)
+    }
+
+    /// Loads the alias table from `<workspace>/prime/aliases.txt`, or an
+    /// empty table if the file doesn't exist yet (e.g. a fresh workspace).
+    pub fn load(workspace: &Path) -> Result<Self> {
+        let path = Self::path(workspace);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read alias file: {}", path.display()))?;
+        let mut aliases = BTreeMap::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some((name, expansion)) = line.split_once('=') {
+                aliases.insert(name.trim().to_string(), expansion.trim().to_string());
+            }
+        }
+        Ok(Self { aliases })
+    }
+
+    /// Persists the current table to `<workspace>/prime/aliases.txt`.
+    fn save(&self, workspace: &Path) -> Result<()> {
+        let path = Self::path(workspace);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+        let content: String = self.aliases.iter().map(|(name, expansion)| format!("{}={}\n", name, expansion)).collect();
+        fs::write(&path, content).with_context(|| format!("Failed to write alias file: {}", path.display()))
+    }
+
+    /// Sets `name` to expand to `expansion`, persisting the change immediately.
+    pub fn set(&mut self, workspace: &Path, name: String, expansion: String) -> Result<()> {
+        self.aliases.insert(name, expansion);
+        self.save(workspace)
+    }
+
+    /// Removes `name`, persisting the change immediately. Returns whether it
+    /// existed beforehand.
+    pub fn clear(&mut self, workspace: &Path, name: &str) -> Result<bool> {
+        let existed = self.aliases.remove(name).is_some();
+        if existed {
+            self.save(workspace)?;
+        }
+        Ok(existed)
+    }
+
+    /// Looks up `name`'s expansion text, if it's a registered alias.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.aliases.get(name).map(|s| s.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases.iter().map(|(name, expansion)| (name.as_str(), expansion.as_str()))
+    }
+}
@@ -1,23 +1,100 @@
 mod processor;
 
 use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-pub use processor::{CommandProcessor, ExecutionStrategy};
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+pub use processor::{CommandProcessor, ExecutionStrategy, DEFAULT_COMMAND_TIMEOUT_SECS};
+pub(crate) use processor::{list_directory_checked, read_file_checked, shell_quote};
+
+/// A single row recalled from the persistent cache (used by `search`).
+pub struct CacheHit {
+    pub command: String,
+    pub exit_code: i32,
+    pub output: String,
+    pub timestamp: i64,
+}
+
+/// Returns the path to the on-disk cache database, e.g. `~/.prime/command_cache.db`.
+fn default_cache_db_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".prime").join("command_cache.db"))
+}
 
 pub struct CommandCache {
     cache: HashMap<String, (i32, String, Instant)>,
     ttl: Duration,
+    db: Option<Connection>,
 }
 
 impl CommandCache {
     pub fn new() -> Self {
-        Self {
-            cache: HashMap::new(),
-            ttl: Duration::from_secs(300), // 5 minutes
-        }
+        Self::with_db_path(default_cache_db_path())
+    }
+
+    /// Builds a cache backed by a SQLite file at `db_path`, populating the in-memory
+    /// map from any unexpired rows already on disk. Passing `None` keeps the cache
+    /// purely in-memory (useful for tests).
+    pub fn with_db_path(db_path: Option<PathBuf>) -> Self {
+        let ttl = Duration::from_secs(300); // 5 minutes
+        let mut cache = HashMap::new();
+
+        let db = db_path.and_then(|path| {
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            match Connection::open(&path) {
+                Ok(conn) => {
+                    if let Err(e) = conn.execute(
+                        "CREATE TABLE IF NOT EXISTS commands (
+                            command TEXT PRIMARY KEY,
+                            exit_code INTEGER NOT NULL,
+                            output TEXT NOT NULL,
+                            timestamp INTEGER NOT NULL
+                        )",
+                        [],
+                    ) {
+                        eprintln!("Warning: Failed to initialize command cache db: {}", e);
+                        return None;
+                    }
+
+                    let now = now_unix();
+                    let ttl_secs = ttl.as_secs() as i64;
+                    if let Ok(mut stmt) = conn.prepare("SELECT command, exit_code, output, timestamp FROM commands") {
+                        if let Ok(rows) = stmt.query_map([], |row| {
+                            Ok((
+                                row.get::<_, String>(0)?,
+                                row.get::<_, i32>(1)?,
+                                row.get::<_, String>(2)?,
+                                row.get::<_, i64>(3)?,
+                            ))
+                        }) {
+                            for row in rows.filter_map(|r| r.ok()) {
+                                let (command, exit_code, output, timestamp) = row;
+                                if now - timestamp < ttl_secs {
+                                    // Reconstruct an Instant equivalent from elapsed seconds so
+                                    // existing TTL logic (`Instant::elapsed`) keeps working.
+                                    let age = Duration::from_secs((now - timestamp).max(0) as u64);
+                                    let reconstructed = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+                                    cache.insert(command, (exit_code, output, reconstructed));
+                                }
+                            }
+                        }
+                    }
+                    Some(conn)
+                }
+                Err(e) => {
+                    eprintln!("Warning: Failed to open command cache db: {}", e);
+                    None
+                }
+            }
+        });
+
+        Self { cache, ttl, db }
     }
-    
+
     pub fn get(&self, command: &str) -> Option<(i32, String)> {
         if let Some((code, output, timestamp)) = self.cache.get(command) {
             if timestamp.elapsed() < self.ttl {
@@ -26,43 +103,100 @@ impl CommandCache {
         }
         None
     }
-    
+
     pub fn set(&mut self, command: String, result: (i32, String)) {
+        if let Some(conn) = &self.db {
+            let write_result: Result<()> = (|| {
+                conn.execute(
+                    "INSERT INTO commands (command, exit_code, output, timestamp)
+                     VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT(command) DO UPDATE SET exit_code=excluded.exit_code, output=excluded.output, timestamp=excluded.timestamp",
+                    rusqlite::params![command, result.0, result.1, now_unix()],
+                )
+                .context("Failed to write command cache row")?;
+                Ok(())
+            })();
+            if let Err(e) = write_result {
+                eprintln!("Warning: Failed to persist command cache entry: {}", e);
+            }
+        }
         self.cache.insert(command, (result.0, result.1, Instant::now()));
     }
-    
+
     pub fn clear_expired(&mut self) {
         self.cache.retain(|_, (_, _, timestamp)| {
             timestamp.elapsed() < self.ttl
         });
+
+        if let Some(conn) = &self.db {
+            let cutoff = now_unix() - self.ttl.as_secs() as i64;
+            if let Err(e) = conn.execute("DELETE FROM commands WHERE timestamp < ?1", rusqlite::params![cutoff]) {
+                eprintln!("Warning: Failed to clear expired command cache rows: {}", e);
+            }
+        }
+    }
+
+    /// Searches persisted commands whose command text or output contains `pattern`,
+    /// most recent first. Returns an empty list when there is no backing database.
+    pub fn search(&self, pattern: &str) -> Vec<CacheHit> {
+        let Some(conn) = &self.db else { return Vec::new() };
+        let like = format!("%{}%", pattern);
+        let mut stmt = match conn.prepare(
+            "SELECT command, exit_code, output, timestamp FROM commands
+             WHERE command LIKE ?1 OR output LIKE ?1
+             ORDER BY timestamp DESC",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return Vec::new(),
+        };
+        let rows = stmt.query_map(rusqlite::params![like], |row| {
+            Ok(CacheHit {
+                command: row.get(0)?,
+                exit_code: row.get(1)?,
+                output: row.get(2)?,
+                timestamp: row.get(3)?,
+            })
+        });
+        match rows {
+            Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+            Err(_) => Vec::new(),
+        }
     }
 }
 
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::thread::sleep;
-    
+    use tempfile::tempdir;
+
     #[test]
     fn test_command_cache() {
-        let mut cache = CommandCache::new();
-        
+        let mut cache = CommandCache::with_db_path(None);
+
         // Test setting and getting
         cache.set("test".to_string(), (0, "output".to_string()));
         assert_eq!(
             cache.get("test"),
             Some((0, "output".to_string()))
         );
-        
+
         // Test expiration
-        let mut cache = CommandCache::new();
+        let mut cache = CommandCache::with_db_path(None);
         cache.ttl = Duration::from_millis(1);
         cache.set("test".to_string(), (0, "output".to_string()));
         sleep(Duration::from_millis(2));
         assert_eq!(cache.get("test"), None);
-        
+
         // Test clear_expired
-        let mut cache = CommandCache::new();
+        let mut cache = CommandCache::with_db_path(None);
         cache.ttl = Duration::from_millis(1);
         cache.set("test1".to_string(), (0, "output1".to_string()));
         cache.set("test2".to_string(), (0, "output2".to_string()));
@@ -70,4 +204,23 @@ mod tests {
         cache.clear_expired();
         assert!(cache.cache.is_empty());
     }
+
+    #[test]
+    fn test_persists_and_searches_across_restarts() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("cache.db");
+
+        {
+            let mut cache = CommandCache::with_db_path(Some(db_path.clone()));
+            cache.set("git status".to_string(), (0, "nothing to commit".to_string()));
+        }
+
+        // A fresh cache pointed at the same file should repopulate from disk.
+        let cache = CommandCache::with_db_path(Some(db_path));
+        assert_eq!(cache.get("git status"), Some((0, "nothing to commit".to_string())));
+
+        let hits = cache.search("commit");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].command, "git status");
+    }
 }
\ No newline at end of file
@@ -1,28 +1,632 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::io::Write;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::fs;
-use anyhow::{Context, Result};
+use std::thread;
+use std::sync::mpsc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Context, Result};
+use glob::Pattern;
 
 #[cfg(target_os = "windows")]
 use tempfile::Builder;
 #[cfg(not(target_os = "windows"))]
 use {tempfile::NamedTempFile, std::os::unix::fs::PermissionsExt};
 
+use crate::config;
 use crate::config_utils;
+use crate::danger_guard::{self, PatternSet};
 use crate::styling::STYLER;
 use super::CommandCache;
 
+const MAX_FILE_READ_LINES: usize = 1000;
+const MAX_FILE_READ_BYTES: u64 = 1_048_576; // 1 MB
+const MAX_DIR_LISTING_CHILDREN_DISPLAY: usize = 20;
+
+/// Default hard timeout applied to a `shell`/custom-tool invocation when the
+/// LLM doesn't name one explicitly, so a wedged or interactive command can't
+/// block the agent loop forever. Pass `timeout=0` to opt out and run
+/// unbounded. `run_script` has no such default: it runs unbounded only when
+/// its `timeout=` argument is omitted.
+pub const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 120;
+
+#[inline]
+fn looks_binary(buf: &[u8]) -> bool {
+    buf.iter().take(256).any(|&b| b == 0)
+}
+
 pub struct ExecutionStrategy {
     primary: String,
     fallbacks: Vec<String>,
 }
 
+/// Maps a `run_script` `lang=` value to its interpreter binary and the file
+/// extension a temp script gets written with.
+struct ScriptRunner;
+
+impl ScriptRunner {
+    fn interpreter_for(lang: &str) -> Result<(&'static str, &'static str)> {
+        match lang {
+            "python" => Ok(("python3", "py")),
+            "node" => Ok(("node", "js")),
+            "bash" => Ok(("bash", "sh")),
+            "pwsh" => Ok(("pwsh", "ps1")),
+            "ruby" => Ok(("ruby", "rb")),
+            "php" => Ok(("php", "php")),
+            other => Err(anyhow!("Unsupported run_script lang '{}': expected python|node|bash|pwsh|ruby|php", other)),
+        }
+    }
+}
+
+/// Incrementally decodes a byte stream as "maybe text", nushell-plugin style:
+/// complete UTF-8 lines are flushed as soon as they're available, a dangling
+/// incomplete multi-byte sequence is held over for the next chunk, and a run
+/// of bytes that's genuinely not valid UTF-8 (not just truncated) is reported
+/// as a `<binary data: N bytes>` placeholder instead of lossy-replacement
+/// garbage.
+struct MaybeTextDecoder {
+    pending: Vec<u8>,
+}
+
+impl MaybeTextDecoder {
+    fn new() -> Self {
+        Self { pending: Vec::new() }
+    }
+
+    /// Feeds a raw chunk, returning any complete lines (and binary-data
+    /// markers) it now has enough bytes to decode. Leftover bytes that don't
+    /// yet form a complete line stay buffered for the next chunk.
+    fn feed(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut out = Vec::new();
+        loop {
+            match std::str::from_utf8(&self.pending) {
+                Ok(text) => {
+                    if let Some(split_at) = text.rfind('\n') {
+                        let complete = &text[..split_at];
+                        out.extend(complete.lines().map(str::to_string));
+                        let remainder = text[split_at + 1..].as_bytes().to_vec();
+                        self.pending = remainder;
+                    }
+                    break;
+                }
+                Err(e) => {
+                    let valid_len = e.valid_up_to();
+                    match e.error_len() {
+                        Some(bad_len) => {
+                            // A binary run follows the valid prefix, so there's no point
+                            // holding back an unterminated trailing line for more bytes
+                            // that will never complete it as text — flush it now.
+                            if valid_len > 0 {
+                                let text = std::str::from_utf8(&self.pending[..valid_len]).unwrap();
+                                out.extend(text.lines().map(str::to_string));
+                            }
+                            out.push(format!("<binary data: {} bytes>", bad_len));
+                            self.pending.drain(..valid_len + bad_len);
+                            // More bytes may already be waiting behind the invalid run.
+                        }
+                        None => {
+                            // Trailing bytes are an incomplete sequence; wait for more.
+                            if valid_len > 0 {
+                                let text = std::str::from_utf8(&self.pending[..valid_len]).unwrap();
+                                if let Some(split_at) = text.rfind('\n') {
+                                    out.extend(text[..split_at].lines().map(str::to_string));
+                                    self.pending.drain(..split_at + 1);
+                                }
+                            }
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Flushes whatever's left once the stream has ended.
+    fn finish(mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        match String::from_utf8(self.pending) {
+            Ok(text) => Some(text),
+            Err(e) => Some(format!("<binary data: {} bytes>", e.as_bytes().len())),
+        }
+    }
+}
+
+/// Reads `reader` to completion, decoding it as "maybe text" and calling
+/// `on_line` with each complete line (plus a final call for any unterminated
+/// trailing text once the stream ends). Shared by `stream_output` (which
+/// prints and collects) and `execute_streamed` (which forwards lines to a
+/// caller-supplied callback instead).
+fn stream_lines(mut reader: impl Read, mut on_line: impl FnMut(String)) {
+    let mut decoder = MaybeTextDecoder::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                for line in decoder.feed(&buf[..n]) {
+                    on_line(line);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+    if let Some(rest) = decoder.finish() {
+        on_line(rest);
+    }
+}
+
+/// Reads `reader` to completion, decoding it as "maybe text": complete lines
+/// are printed live (via `style`) as they arrive and accumulated into the
+/// returned string, so long-running commands show progress instead of going
+/// silent until exit.
+fn stream_output(reader: impl Read, style: impl Fn(&str) -> String) -> String {
+    let mut captured = String::new();
+    stream_lines(reader, |line| {
+        println!("{}", style(&line));
+        captured.push_str(&line);
+        captured.push('\n');
+    });
+    captured
+}
+
+/// Which pipe a line handed to `execute_streamed`'s callback came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Stdout,
+    Stderr,
+}
+
+/// Outcome of `execute_streamed`. Unlike the plain `(i32, String)` pair
+/// `execute_command` returns, `exit_code` stays `None` whenever the process
+/// didn't get to report one on its own — killed by a signal, or killed by us
+/// because `timed_out` fired — so callers can tell "ran and failed" apart
+/// from "never finished".
+pub struct StreamedOutput {
+    pub exit_code: Option<i32>,
+    pub output: String,
+    pub timed_out: bool,
+}
+
+/// Kills `child` and everything it spawned. `child` must have been built
+/// with `ShellCommand::group(true)` (which makes its PID double as its own
+/// process group ID), so a `kill -KILL -<pgid>` reaches the whole tree
+/// instead of leaving grandchildren (e.g. a shell's own children) running
+/// past the timeout. Windows has no equivalent concept, so it falls back to
+/// killing just the direct child.
+#[cfg(not(target_os = "windows"))]
+fn kill_process_group(child: &mut std::process::Child) {
+    let pgid = child.id();
+    let _ = Command::new("kill").args(["-KILL", &format!("-{}", pgid)]).status();
+    let _ = child.kill();
+}
+
+#[cfg(target_os = "windows")]
+fn kill_process_group(child: &mut std::process::Child) {
+    let _ = child.kill();
+}
+
+/// Builds a shell invocation, centralizing the platform-specific process
+/// spawning shared by `execute_command_internal` and `execute_script` (the
+/// latter goes through the former via `execute_command`), plus optional
+/// privilege elevation for commands the user has explicitly approved
+/// running with elevated rights after an `is_ask_me_before_command` prompt.
+struct ShellCommand {
+    program: String,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    envs: BTreeMap<String, String>,
+    elevate: bool,
+    group: bool,
+}
+
+impl ShellCommand {
+    fn new(program: impl Into<String>, working_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            working_dir: working_dir.into(),
+            envs: BTreeMap::new(),
+            elevate: false,
+            group: false,
+        }
+    }
+
+    fn args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    fn envs(mut self, envs: BTreeMap<String, String>) -> Self {
+        self.envs = envs;
+        self
+    }
+
+    fn elevate(mut self, elevate: bool) -> Self {
+        self.elevate = elevate;
+        self
+    }
+
+    /// Spawns the child as the leader of its own process group (Unix only),
+    /// so a timeout can kill the whole tree it spawned instead of just the
+    /// immediate child, which would otherwise leak orphaned grandchildren.
+    fn group(mut self, group: bool) -> Self {
+        self.group = group;
+        self
+    }
+
+    /// Assembles the `std::process::Command`, prepending a privilege
+    /// escalation helper when `elevate` is set: `sudo` (falling back to
+    /// `doas` if `sudo` isn't on `$PATH`) on Unix, or a `Start-Process
+    /// -Verb RunAs` relaunch through PowerShell on Windows, which surfaces
+    /// the native UAC prompt.
+    fn build(&self) -> Command {
+        let mut cmd = if self.elevate { self.build_elevated() } else {
+            let mut cmd = Command::new(&self.program);
+            cmd.args(&self.args)
+                .current_dir(&self.working_dir)
+                .envs(&self.envs)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            cmd
+        };
+        #[cfg(not(target_os = "windows"))]
+        if self.group {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+        cmd
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn build_elevated(&self) -> Command {
+        let helper = if Command::new("which")
+            .arg("sudo")
+            .output()
+            .map(|o| o.status.success())
+            .unwrap_or(false)
+        {
+            "sudo"
+        } else {
+            "doas"
+        };
+        let mut cmd = Command::new(helper);
+        cmd.arg(&self.program)
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .envs(&self.envs)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    #[cfg(target_os = "windows")]
+    fn build_elevated(&self) -> Command {
+        let inner_args = self
+            .args
+            .iter()
+            .map(|a| format!("'{}'", a.replace('\'', "''")))
+            .collect::<Vec<_>>()
+            .join(",");
+        let arg_list = if inner_args.is_empty() {
+            String::new()
+        } else {
+            format!(" -ArgumentList {}", inner_args)
+        };
+        let ps_command = format!(
+            "Start-Process -FilePath '{}'{} -Verb RunAs -Wait",
+            self.program, arg_list
+        );
+        let mut cmd = Command::new("powershell");
+        cmd.args(["-Command", &ps_command])
+            .current_dir(&self.working_dir)
+            .envs(&self.envs)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    fn spawn(&self) -> std::io::Result<std::process::Child> {
+        self.build().spawn()
+    }
+}
+
+/// One `&&`/`;`-separated step of a compound command line, paired with
+/// whether the line requires it to run only after the previous step
+/// succeeded (`&&`) or unconditionally (`;`, and the first step).
+struct SequentialStep {
+    command: String,
+    requires_previous_success: bool,
+}
+
+/// Splits `command` on top-level `&&` and `;` into its sequential steps,
+/// classified nushell-pipeline style so the caller can stop the chain early
+/// on an `&&` failure while still running every `;`-separated step.
+fn split_sequential(command: &str) -> Vec<SequentialStep> {
+    let mut steps = Vec::new();
+    let mut rest = command;
+    let mut requires_previous_success = false;
+    loop {
+        let next_and = rest.find("&&");
+        let next_semi = rest.find(';');
+        let split_at = match (next_and, next_semi) {
+            (Some(a), Some(s)) => Some(a.min(s)),
+            (Some(a), None) => Some(a),
+            (None, Some(s)) => Some(s),
+            (None, None) => None,
+        };
+        match split_at {
+            Some(idx) => {
+                let is_and = rest[idx..].starts_with("&&");
+                let piece = rest[..idx].trim();
+                if !piece.is_empty() {
+                    steps.push(SequentialStep { command: piece.to_string(), requires_previous_success });
+                }
+                rest = &rest[idx + if is_and { 2 } else { 1 }..];
+                requires_previous_success = is_and;
+            }
+            None => {
+                let piece = rest.trim();
+                if !piece.is_empty() {
+                    steps.push(SequentialStep { command: piece.to_string(), requires_previous_success });
+                }
+                break;
+            }
+        }
+    }
+    steps
+}
+
+/// Splits one sequential step on top-level `|` into its pipeline stages,
+/// skipping over `||` (shell OR, which this doesn't implement) so it isn't
+/// mistaken for a pipe.
+fn split_pipeline(step: &str) -> Vec<String> {
+    let bytes = step.as_bytes();
+    let mut stages = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'|' {
+            let is_double = (i + 1 < bytes.len() && bytes[i + 1] == b'|') || (i > 0 && bytes[i - 1] == b'|');
+            if is_double {
+                i += 1;
+            } else {
+                stages.push(step[start..i].trim().to_string());
+                start = i + 1;
+            }
+        }
+        i += 1;
+    }
+    stages.push(step[start..].trim().to_string());
+    stages
+}
+
+/// Which Deno-style capability a `run`/`read`/`write` request is asking for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Run,
+    Read,
+    Write,
+}
+
+impl Capability {
+    fn label(self) -> &'static str {
+        match self {
+            Capability::Run => "run",
+            Capability::Read => "read",
+            Capability::Write => "write",
+        }
+    }
+}
+
+/// Outcome of checking a capability request against `PermissionSet`'s allow/deny
+/// lists: a `deny-*` entry always wins (even under `allow_all`), an `allow-*`
+/// entry or `allow_all` grants it, and anything else is `Undecided` and must be
+/// resolved with an interactive prompt.
+#[derive(Debug, PartialEq, Eq)]
+enum PermissionCheck {
+    Allowed,
+    Denied,
+    Undecided,
+}
+
+/// Allow/deny entries for one capability. `run` entries are plain strings (a
+/// program name); `read`/`write` entries are matched as shell-style globs
+/// (compiled on each check) so `./src/**` matches the way a user expects.
+#[derive(Default)]
+struct CapabilityRules {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl CapabilityRules {
+    fn check(&self, value: &str, matches: impl Fn(&str, &str) -> bool) -> PermissionCheck {
+        if self.deny.iter().any(|p| matches(p, value)) {
+            PermissionCheck::Denied
+        } else if self.allow.iter().any(|p| matches(p, value)) {
+            PermissionCheck::Allowed
+        } else {
+            PermissionCheck::Undecided
+        }
+    }
+}
+
+/// A structured, Deno-style capability sandbox: allow/deny lists for `run`
+/// (program names) and `read`/`write` (path globs), parsed from
+/// `~/.prime/permissions.txt` plus `--allow-*`/`--deny-*` CLI flags.
+/// Replaces a bare `command.contains(pattern)` scan with rules that can't be
+/// fooled by extra whitespace or an aliased binary name, and that the user
+/// can audit and extend without touching Rust code.
+pub struct PermissionSet {
+    run: CapabilityRules,
+    read: CapabilityRules,
+    write: CapabilityRules,
+    allow_all: bool,
+}
+
+impl Default for PermissionSet {
+    fn default() -> Self {
+        Self {
+            run: CapabilityRules::default(),
+            read: CapabilityRules::default(),
+            write: CapabilityRules::default(),
+            allow_all: false,
+        }
+    }
+}
+
+impl PermissionSet {
+    /// Builds a `PermissionSet` from `~/.prime/permissions.txt` rules (lowest
+    /// precedence) overlaid with CLI flags like `--allow-run=git,npm` or
+    /// `--allow-all` (highest precedence), both expressed as the same
+    /// `allow-<cap>=a,b,c` / `deny-<cap>=a,b,c` / `allow-all` directives.
+    pub fn parse<'a>(config_rules: &[String], cli_args: impl Iterator<Item = &'a str>) -> Self {
+        let mut set = Self::default();
+        for rule in config_rules {
+            set.apply_rule(rule);
+        }
+        for arg in cli_args {
+            if let Some(rule) = arg.strip_prefix("--") {
+                set.apply_rule(rule);
+            }
+        }
+        set
+    }
+
+    fn apply_rule(&mut self, rule: &str) {
+        let rule = rule.trim();
+        if rule == "allow-all" {
+            self.allow_all = true;
+            return;
+        }
+        let Some((key, value)) = rule.split_once('=') else { return };
+        let entries: Vec<String> = value.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect();
+        let rules = match key {
+            "allow-run" => &mut self.run.allow,
+            "deny-run" => &mut self.run.deny,
+            "allow-read" => &mut self.read.allow,
+            "deny-read" => &mut self.read.deny,
+            "allow-write" => &mut self.write.allow,
+            "deny-write" => &mut self.write.deny,
+            _ => return,
+        };
+        rules.extend(entries);
+    }
+
+    /// Checks a program name (already reduced to its file stem, e.g. `git`
+    /// from `/usr/bin/git`) against the `run` capability.
+    fn check_run(&self, program: &str) -> PermissionCheck {
+        let check = self.run.check(program, |pattern, value| pattern.eq_ignore_ascii_case(value));
+        self.with_allow_all(check)
+    }
+
+    /// Checks a filesystem path against the `read`/`write` capability's glob
+    /// patterns. Falls back to a plain substring match when a rule isn't a
+    /// valid glob, so e.g. a bare directory name still works as a rule.
+    fn check_path(&self, capability: Capability, path: &str) -> PermissionCheck {
+        let rules = match capability {
+            Capability::Read => &self.read,
+            Capability::Write => &self.write,
+            _ => unreachable!("check_path only called for Read/Write"),
+        };
+        let check = rules.check(path, |pattern, value| {
+            Pattern::new(pattern).map(|g| g.matches(value)).unwrap_or(false) || value.contains(pattern)
+        });
+        self.with_allow_all(check)
+    }
+
+    /// `allow_all` only upgrades an `Undecided` verdict — an explicit `deny-*`
+    /// rule still wins, so `--allow-all --deny-write=/etc` behaves as expected.
+    fn with_allow_all(&self, check: PermissionCheck) -> PermissionCheck {
+        if check == PermissionCheck::Undecided && self.allow_all {
+            PermissionCheck::Allowed
+        } else {
+            check
+        }
+    }
+
+    /// Records an "allow always" decision so the rest of the session no
+    /// longer prompts for this exact value.
+    fn grant(&mut self, capability: Capability, value: String) {
+        match capability {
+            Capability::Run => self.run.allow.push(value),
+            Capability::Read => self.read.allow.push(value),
+            Capability::Write => self.write.allow.push(value),
+        }
+    }
+}
+
+/// Splits a command line into the program names it would invoke: the leading
+/// word of every `&&`/`;`/`|`-separated stage (reusing the same splitters
+/// `execute_pipeline` does) plus the leading word of every `$(...)`
+/// command substitution, wherever it appears. This is what `run` capability
+/// checks are matched against, so a `rm -rf` hidden inside `echo $(rm -rf /)`
+/// still gets caught.
+fn extract_invoked_programs(command: &str) -> Vec<String> {
+    let mut programs = Vec::new();
+    for step in split_sequential(command) {
+        for stage in split_pipeline(&step.command) {
+            if let Some(program) = program_name_of(&stage) {
+                programs.push(program);
+            }
+        }
+    }
+    let bytes = command.as_bytes();
+    let mut i = 0;
+    while let Some(start) = command[i..].find("$(") {
+        let open = i + start + 2;
+        if let Some(len) = command[open..].find(')') {
+            let inner = &command[open..open + len];
+            if let Some(program) = program_name_of(inner) {
+                programs.push(program);
+            }
+            i = open + len + 1;
+        } else {
+            break;
+        }
+        if i >= bytes.len() {
+            break;
+        }
+    }
+    programs
+}
+
+/// Extracts the leading whitespace-delimited word of `segment` and reduces it
+/// to a bare program name (dropping any directory components), the same way
+/// `config_utils::Shell::from_name` reduces a shell path to its family.
+fn program_name_of(segment: &str) -> Option<String> {
+    let first = segment.trim().split_whitespace().next()?;
+    Path::new(first).file_stem().map(|s| s.to_string_lossy().to_string())
+}
+
+/// Quotes `value` as a single argument of the platform's configured shell
+/// (`sh -c` on Unix, `powershell -Command` on Windows), so a space or shell
+/// metacharacter inside a path interpolated into a `format!`-built command
+/// string can't be re-split or interpreted by the shell.
+#[cfg(not(target_os = "windows"))]
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+#[cfg(target_os = "windows")]
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
 pub struct CommandProcessor {
     command_cache: CommandCache,
     shell_command: String,
     shell_args: Vec<String>,
-    ask_me_before_patterns: Vec<String>,
+    danger_guard: PatternSet,
+    aliases: BTreeMap<String, String>,
+    env_vars: BTreeMap<String, String>,
+    permissions: PermissionSet,
+    ignored_path_patterns: Vec<Pattern>,
 }
 
 impl CommandProcessor {
@@ -34,28 +638,106 @@ impl CommandProcessor {
         #[cfg(not(target_os = "windows"))]
         let (shell_command, shell_args) = ("sh".to_string(), vec!["-c".to_string()]);
 
-        let ask_me_before_patterns = config_utils::load_ask_me_before_patterns().unwrap_or_else(|e| {
+        let detected_shell = config_utils::Shell::detect();
+        let ask_me_before_patterns = config_utils::load_ask_me_before_patterns_for_shell(detected_shell)
+            .unwrap_or_else(|e| {
+                eprintln!(
+                    "{}",
+                    STYLER.error_style(format!(
+                        "Warning: Failed to load 'ask me before' patterns: {}. Using defaults.",
+                        e
+                    ))
+                );
+                config_utils::DEFAULT_ASK_ME_BEFORE_PATTERNS
+                    .iter()
+                    .map(|s| (0, s.to_string()))
+                    .collect()
+            });
+
+        let (danger_guard, pattern_errors) = PatternSet::compile(&ask_me_before_patterns);
+        for err in &pattern_errors {
+            let location = if err.line == 0 {
+                "built-in default".to_string()
+            } else {
+                format!("ask_me_before_patterns.txt:{}", err.line)
+            };
             eprintln!(
                 "{}",
                 STYLER.error_style(format!(
-                    "Warning: Failed to load 'ask me before' patterns: {}. Using defaults.",
-                    e
+                    "Warning: {}: failed to compile pattern '{}': {}",
+                    location, err.pattern, err.message
                 ))
             );
-            config_utils::DEFAULT_ASK_ME_BEFORE_PATTERNS
-                .iter()
-                .map(|s| s.to_string())
-                .collect()
+        }
+
+        let aliases = config_utils::load_aliases().unwrap_or_else(|e| {
+            eprintln!("{}", STYLER.error_style(format!("Warning: Failed to load aliases: {}. Starting with none.", e)));
+            BTreeMap::new()
+        });
+
+        let env_vars = config_utils::load_env_vars().unwrap_or_else(|e| {
+            eprintln!("{}", STYLER.error_style(format!("Warning: Failed to load persistent environment: {}. Starting with none.", e)));
+            BTreeMap::new()
+        });
+
+        let permission_rules = config_utils::load_permission_rules().unwrap_or_else(|e| {
+            eprintln!("{}", STYLER.error_style(format!("Warning: Failed to load permission rules: {}. Starting with none.", e)));
+            Vec::new()
+        });
+        let permissions = PermissionSet::parse(&permission_rules, std::env::args().skip(1).collect::<Vec<_>>().iter().map(|s| s.as_str()));
+
+        let ignored_path_patterns = config::load_ignored_path_patterns().unwrap_or_else(|e| {
+            eprintln!("{}", STYLER.error_style(format!("Warning: Failed to load ignored path patterns: {}. Using defaults.", e)));
+            config::DEFAULT_IGNORED_PATHS.iter().filter_map(|s| Pattern::new(s).ok()).collect()
         });
 
         Self {
             command_cache: CommandCache::new(),
             shell_command,
             shell_args,
-            ask_me_before_patterns,
+            danger_guard,
+            aliases,
+            env_vars,
+            permissions,
+            ignored_path_patterns,
         }
     }
 
+    /// Expands `command`'s first whitespace-delimited token against the alias
+    /// table, leaving the rest of the command untouched. A command whose
+    /// first token isn't an alias passes through unchanged.
+    fn expand_alias(&self, command: &str) -> String {
+        let Some((first, rest)) = command.split_once(char::is_whitespace) else {
+            return self.aliases.get(command).cloned().unwrap_or_else(|| command.to_string());
+        };
+        match self.aliases.get(first) {
+            Some(expansion) => format!("{} {}", expansion, rest),
+            None => command.to_string(),
+        }
+    }
+
+    /// Registers (or overwrites) an alias and persists the table to
+    /// `~/.prime/aliases.toml`.
+    pub fn set_alias(&mut self, name: &str, expansion: &str) -> Result<()> {
+        self.aliases.insert(name.to_string(), expansion.to_string());
+        config_utils::save_aliases(&self.aliases)
+    }
+
+    pub fn list_aliases(&self) -> &BTreeMap<String, String> {
+        &self.aliases
+    }
+
+    /// Sets a persistent environment variable, injected into every spawned
+    /// command, and persists the table to `~/.prime/env.toml`.
+    pub fn set_env_var(&mut self, key: &str, value: &str) -> Result<()> {
+        self.env_vars.insert(key.to_string(), value.to_string());
+        config_utils::save_env_vars(&self.env_vars)
+    }
+
+    pub fn list_env_vars(&self) -> &BTreeMap<String, String> {
+        &self.env_vars
+    }
+
     pub fn get_execution_strategies(&self, command: &str) -> Vec<String> {
         let mut strategies = vec![command.to_string()];
         
@@ -91,24 +773,30 @@ impl CommandProcessor {
                 }
             }
             
-            let result = self.execute_command_internal(strategy, working_dir)?;
+            let result = self.execute_command_internal(strategy, working_dir, false)?;
             if result.0 == 0 {
                 return Ok(result);
             }
-            
+
             if idx < strategies.len() - 1 {
                 eprintln!("Strategy {} failed, trying next...", idx + 1);
             }
         }
-        
+
         // Return last failure if all strategies failed
-        self.execute_command_internal(command, working_dir)
+        self.execute_command_internal(command, working_dir, false)
     }
 
-    fn execute_command_internal(&mut self, command: &str, working_dir: Option<&Path>) -> Result<(i32, String)> {
-        // Check cache first
-        if let Some(cached_result) = self.command_cache.get(command) {
-            return Ok(cached_result);
+    fn execute_command_internal(&mut self, command: &str, working_dir: Option<&Path>, elevate: bool) -> Result<(i32, String)> {
+        let command = self.expand_alias(command);
+        let command = command.as_str();
+
+        // Check cache first (an elevated run is a deliberate one-off, so it
+        // skips the cache in both directions)
+        if !elevate {
+            if let Some(cached_result) = self.command_cache.get(command) {
+                return Ok(cached_result);
+            }
         }
 
         let effective_working_dir = working_dir.unwrap_or_else(|| Path::new("."));
@@ -116,7 +804,8 @@ impl CommandProcessor {
         println!(
             "{}",
             STYLER.command_exec_style(format!(
-                "Executing in '{}': {}",
+                "Executing{} in '{}': {}",
+                if elevate { " (elevated)" } else { "" },
                 effective_working_dir.display(),
                 command
             ))
@@ -125,17 +814,30 @@ impl CommandProcessor {
         let mut args = self.shell_args.clone();
         args.push(command.to_string());
 
-        let output = Command::new(&self.shell_command)
-            .args(&args)
-            .current_dir(effective_working_dir)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
+        let mut child = ShellCommand::new(&self.shell_command, effective_working_dir)
+            .args(args)
+            .envs(self.env_vars.clone())
+            .elevate(elevate)
+            .spawn()
             .with_context(|| format!("Failed to execute command: {}", command))?;
 
-        let exit_code = output.status.code().unwrap_or(-1);
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let child_stdout = child.stdout.take().expect("child spawned with piped stdout");
+        let child_stderr = child.stderr.take().expect("child spawned with piped stderr");
+
+        let stdout_thread = thread::spawn(move || {
+            stream_output(child_stdout, |line| STYLER.dim_gray_style(line).to_string())
+        });
+        let stderr_thread = thread::spawn(move || {
+            stream_output(child_stderr, |line| STYLER.error_style(line).to_string())
+        });
+
+        let status = child
+            .wait()
+            .with_context(|| format!("Failed to wait on command: {}", command))?;
+        let stdout = stdout_thread.join().unwrap_or_default();
+        let stderr = stderr_thread.join().unwrap_or_default();
+
+        let exit_code = status.code().unwrap_or(-1);
 
         let result = if stderr.is_empty() {
             (exit_code, stdout)
@@ -152,28 +854,356 @@ impl CommandProcessor {
                 exit_code
             ))
         );
-        
-        let preview = result.1.lines().take(5).collect::<Vec<&str>>().join("\n");
-        if !preview.is_empty() {
+
+        // Cache successful results
+        if exit_code == 0 && !elevate {
+            self.command_cache.set(command.to_string(), result.clone());
+        }
+
+        Ok(result)
+    }
+
+    /// Spawns `command` with piped stdout/stderr, draining both pipes on
+    /// reader threads and forwarding each decoded line to `on_line` as it
+    /// arrives, tagged with which stream it came from. When `timeout` fires
+    /// before the child exits, kills its entire process group and returns
+    /// with `timed_out: true` and whatever output was captured up to that
+    /// point, instead of blocking indefinitely on a wedged process (or
+    /// leaving its children running past the deadline). `None` means no
+    /// timeout. This is the primitive `execute_command_with_timeout` and the
+    /// script runner build on; it does not touch the command cache or
+    /// `ask_me_before`/permission checks, which are the caller's job.
+    pub fn execute_streamed(
+        &mut self,
+        command: &str,
+        working_dir: Option<&Path>,
+        timeout: Option<Duration>,
+        elevate: bool,
+        mut on_line: impl FnMut(StreamKind, &str),
+    ) -> Result<StreamedOutput> {
+        let effective_working_dir = working_dir.unwrap_or_else(|| Path::new("."));
+
+        let mut args = self.shell_args.clone();
+        args.push(command.to_string());
+
+        let mut child = ShellCommand::new(&self.shell_command, effective_working_dir)
+            .args(args)
+            .envs(self.env_vars.clone())
+            .elevate(elevate)
+            .group(true)
+            .spawn()
+            .with_context(|| format!("Failed to execute command: {}", command))?;
+
+        let child_stdout = child.stdout.take().expect("child spawned with piped stdout");
+        let child_stderr = child.stderr.take().expect("child spawned with piped stderr");
+
+        let (tx, rx) = mpsc::channel::<(StreamKind, String)>();
+        let stdout_tx = tx.clone();
+        let stdout_thread = thread::spawn(move || {
+            stream_lines(child_stdout, |line| {
+                let _ = stdout_tx.send((StreamKind::Stdout, line));
+            });
+        });
+        let stderr_thread = thread::spawn(move || {
+            stream_lines(child_stderr, |line| {
+                let _ = tx.send((StreamKind::Stderr, line));
+            });
+        });
+
+        let deadline = timeout.map(|d| Instant::now() + d);
+        let mut output = String::new();
+        let mut timed_out = false;
+
+        loop {
+            let next = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) => rx.recv_timeout(remaining),
+                    None => Err(mpsc::RecvTimeoutError::Timeout),
+                },
+                None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+            };
+
+            match next {
+                Ok((kind, line)) => {
+                    on_line(kind, &line);
+                    output.push_str(&line);
+                    output.push('\n');
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    timed_out = true;
+                    kill_process_group(&mut child);
+                    break;
+                }
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        // Drain whatever the reader threads already had queued up before the
+        // kill landed, so a timeout doesn't throw away the last few lines.
+        while let Ok((kind, line)) = rx.try_recv() {
+            on_line(kind, &line);
+            output.push_str(&line);
+            output.push('\n');
+        }
+        let _ = stdout_thread.join();
+        let _ = stderr_thread.join();
+
+        let exit_code = if timed_out {
+            let _ = child.wait();
+            None
+        } else {
+            child.wait().ok().and_then(|status| status.code())
+        };
+
+        Ok(StreamedOutput { exit_code, output, timed_out })
+    }
+
+    /// Thin wrapper over `execute_streamed` that collects the stream into the
+    /// same `(i32, String)` shape `execute_command` returns, for callers that
+    /// just want a hard timeout without consuming output incrementally. A
+    /// timeout is reported as exit code `-1` with a trailing
+    /// `[timed out after Ns]` marker appended to the output, mirroring how a
+    /// signal-killed process already surfaces as `-1`.
+    pub fn execute_command_with_timeout(&mut self, command: &str, working_dir: Option<&Path>, timeout: Duration, elevate: bool) -> Result<(i32, String)> {
+        let result = self.execute_streamed(command, working_dir, Some(timeout), elevate, |kind, line| {
+            let styled = match kind {
+                StreamKind::Stdout => STYLER.dim_gray_style(line).to_string(),
+                StreamKind::Stderr => STYLER.error_style(line).to_string(),
+            };
+            println!("{}", styled);
+        })?;
+
+        let mut output = result.output;
+        if result.timed_out {
+            output.push_str(&format!("[timed out after {}s]", timeout.as_secs_f64()));
+        }
+        Ok((result.exit_code.unwrap_or(-1), output))
+    }
+
+    /// Implements the `run_script` primeactions tool: writes `script_content`
+    /// to a uniquely-named temp file under `working_dir` for the requested
+    /// `lang`, invokes the matching interpreter (passing through any `args=`
+    /// the LLM supplied) with `timeout` enforced via
+    /// `execute_command_with_timeout`, and removes the temp file afterward
+    /// regardless of how the script exited.
+    pub fn run_script(
+        &mut self,
+        lang: &str,
+        args: Option<&str>,
+        timeout: Option<Duration>,
+        script_content: &str,
+        working_dir: Option<&Path>,
+    ) -> Result<(i32, String)> {
+        static SCRIPT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let (interpreter, extension) = ScriptRunner::interpreter_for(lang)?;
+        let effective_working_dir = working_dir.unwrap_or_else(|| Path::new(".")).to_path_buf();
+        let unique = SCRIPT_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let script_path = effective_working_dir.join(format!(".prime_script_{}_{}.{}", std::process::id(), unique, extension));
+
+        fs::write(&script_path, script_content).with_context(|| format!("Failed to write temp script file: {}", script_path.display()))?;
+        #[cfg(not(target_os = "windows"))]
+        {
+            if let Ok(metadata) = fs::metadata(&script_path) {
+                let mut perms = metadata.permissions();
+                perms.set_mode(0o755);
+                let _ = fs::set_permissions(&script_path, perms);
+            }
+        }
+
+        let mut command = format!("{} {}", interpreter, script_path.display());
+        if let Some(args) = args {
+            command.push(' ');
+            command.push_str(args);
+        }
+
+        let result = match timeout {
+            Some(timeout) => self.execute_command_with_timeout(&command, Some(&effective_working_dir), timeout, false),
+            None => self.execute_command_internal(&command, Some(&effective_working_dir), false),
+        };
+
+        let _ = fs::remove_file(&script_path);
+        result
+    }
+
+    /// Runs a compound command line (one containing `&&`, `;`, or `|`) as a
+    /// classified pipeline instead of bailing out to a single plain shell
+    /// invocation: each `&&`/`;` step is split into its `|`-connected
+    /// stages, every stage still goes through `get_execution_strategies`
+    /// and the per-command cache, and a stage's stdout is piped straight
+    /// into the next stage's stdin. The overall exit code is the last step's
+    /// (or the first `&&` failure that stopped the chain).
+    fn execute_pipeline(&mut self, command: &str, working_dir: Option<&Path>) -> Result<(i32, String)> {
+        let steps = split_sequential(command);
+        let mut last_result = (0, String::new());
+        let mut combined_output = String::new();
+        for step in steps {
+            if step.requires_previous_success && last_result.0 != 0 {
+                break;
+            }
+            let stages = split_pipeline(&step.command);
+            last_result = self.execute_pipeline_stages(&stages, working_dir)?;
+            if !combined_output.is_empty() {
+                combined_output.push('\n');
+            }
+            combined_output.push_str(&last_result.1);
+        }
+        Ok((last_result.0, combined_output))
+    }
+
+    /// Runs a single `|`-connected chain of stages, feeding each stage's
+    /// captured stdout into the next stage's stdin. Only the final stage's
+    /// output is streamed live to the terminal; intermediate stages are
+    /// plumbing and are captured quietly.
+    fn execute_pipeline_stages(&mut self, stages: &[String], working_dir: Option<&Path>) -> Result<(i32, String)> {
+        let mut stdin_input: Option<String> = None;
+        let mut result = (0, String::new());
+        for (idx, stage) in stages.iter().enumerate() {
+            let is_last = idx == stages.len() - 1;
+            result = self.execute_pipeline_leaf(stage, working_dir, stdin_input.as_deref(), is_last)?;
+            if result.0 != 0 {
+                return Ok(result);
+            }
+            stdin_input = Some(result.1.clone());
+        }
+        Ok(result)
+    }
+
+    /// Runs one pipeline stage through `get_execution_strategies`, caching a
+    /// successful result the same way `execute_with_fallbacks` does.
+    /// Skips the cache entirely when fed from a previous stage's stdout,
+    /// since that output is specific to this one pipeline run.
+    fn execute_pipeline_leaf(
+        &mut self,
+        command: &str,
+        working_dir: Option<&Path>,
+        stdin_input: Option<&str>,
+        print_output: bool,
+    ) -> Result<(i32, String)> {
+        let strategies = self.get_execution_strategies(command);
+        let mut last_result = (0, String::new());
+        for (idx, strategy) in strategies.iter().enumerate() {
+            if stdin_input.is_none() {
+                if let Some(cached) = self.command_cache.get(strategy) {
+                    if cached.0 == 0 {
+                        return Ok(cached);
+                    }
+                }
+            }
+
+            last_result = self.run_piped_stage(strategy, working_dir, stdin_input, print_output)?;
+            if last_result.0 == 0 {
+                if stdin_input.is_none() {
+                    self.command_cache.set(strategy.clone(), last_result.clone());
+                }
+                return Ok(last_result);
+            }
+
+            if idx < strategies.len() - 1 {
+                eprintln!("Strategy {} failed, trying next...", idx + 1);
+            }
+        }
+        Ok(last_result)
+    }
+
+    /// Spawns one pipeline stage, optionally writing `stdin_input` to its
+    /// stdin. The final stage (`print_output`) streams live like
+    /// `execute_command_internal`; earlier stages are captured silently
+    /// since their output is only meant for the next stage, not the screen.
+    fn run_piped_stage(
+        &self,
+        command: &str,
+        working_dir: Option<&Path>,
+        stdin_input: Option<&str>,
+        print_output: bool,
+    ) -> Result<(i32, String)> {
+        let effective_working_dir = working_dir.unwrap_or_else(|| Path::new("."));
+
+        if print_output {
             println!(
                 "{}",
-                STYLER.dim_gray_style(format!("Output preview:\n{}", preview))
+                STYLER.command_exec_style(format!(
+                    "Executing in '{}': {}",
+                    effective_working_dir.display(),
+                    command
+                ))
             );
-            if result.1.lines().count() > 5 {
-                println!(
-                    "{}",
-                    STYLER.dim_gray_style(
-                        "... (output truncated, full output saved in conversation)"
-                    )
-                );
-            }
         }
 
-        // Cache successful results
-        if exit_code == 0 {
-            self.command_cache.set(command.to_string(), result.clone());
+        let mut args = self.shell_args.clone();
+        args.push(command.to_string());
+
+        let mut child = Command::new(&self.shell_command)
+            .args(&args)
+            .current_dir(effective_working_dir)
+            .envs(&self.env_vars)
+            .stdin(if stdin_input.is_some() { Stdio::piped() } else { Stdio::null() })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to execute pipeline stage: {}", command))?;
+
+        // Writes on a dedicated thread so a child that starts emitting stdout
+        // before it's finished reading stdin (e.g. `sort`, `grep` on input
+        // past the OS pipe buffer's ~64KB) can't deadlock this thread, which
+        // would otherwise block in `write_all` while the child blocks writing
+        // stdout that nobody's draining yet.
+        let stdin_thread = stdin_input.map(|input| {
+            let input = input.to_string();
+            let mut stdin = child.stdin.take().expect("child spawned with piped stdin");
+            thread::spawn(move || {
+                stdin.write_all(input.as_bytes()).ok();
+            })
+        });
+
+        let child_stdout = child.stdout.take().expect("child spawned with piped stdout");
+        let child_stderr = child.stderr.take().expect("child spawned with piped stderr");
+
+        let (stdout, stderr, status) = if print_output {
+            let stdout_thread = thread::spawn(move || {
+                stream_output(child_stdout, |line| STYLER.dim_gray_style(line).to_string())
+            });
+            let stderr_thread = thread::spawn(move || {
+                stream_output(child_stderr, |line| STYLER.error_style(line).to_string())
+            });
+            let status = child
+                .wait()
+                .with_context(|| format!("Failed to wait on pipeline stage: {}", command))?;
+            (stdout_thread.join().unwrap_or_default(), stderr_thread.join().unwrap_or_default(), status)
+        } else {
+            let mut stdout_buf = String::new();
+            let mut stderr_buf = String::new();
+            let mut child_stdout = child_stdout;
+            let mut child_stderr = child_stderr;
+            child_stdout.read_to_string(&mut stdout_buf).ok();
+            child_stderr.read_to_string(&mut stderr_buf).ok();
+            let status = child
+                .wait()
+                .with_context(|| format!("Failed to wait on pipeline stage: {}", command))?;
+            (stdout_buf, stderr_buf, status)
+        };
+
+        if let Some(stdin_thread) = stdin_thread {
+            let _ = stdin_thread.join();
         }
-        
+
+        let exit_code = status.code().unwrap_or(-1);
+
+        let result = if stderr.is_empty() {
+            (exit_code, stdout)
+        } else if stdout.is_empty() {
+            (exit_code, format!("STDERR:\n{}", stderr))
+        } else {
+            (exit_code, format!("{}\n\nSTDERR:\n{}", stdout, stderr))
+        };
+
+        if print_output {
+            println!(
+                "{}",
+                STYLER.dim_gray_style(format!("Command completed with exit code: {}", exit_code))
+            );
+        }
+
         Ok(result)
     }
 
@@ -212,26 +1242,344 @@ impl CommandProcessor {
     }
 
     pub fn is_ask_me_before_command(&self, command: &str) -> bool {
-        let command_lower = command.trim().to_lowercase();
-        self.ask_me_before_patterns
-            .iter()
-            .any(|pattern| command_lower.contains(&pattern.to_lowercase()))
+        self.danger_guard.matches(&danger_guard::normalize(command)).is_some()
+    }
+
+    /// Like `is_ask_me_before_command`, but also returns the pattern that
+    /// matched, so callers can explain to the user why they're being prompted.
+    pub fn matched_danger_pattern(&self, command: &str) -> Option<&str> {
+        self.danger_guard
+            .matches(&danger_guard::normalize(command))
+            .map(|p| p.source.as_str())
     }
 
     pub fn execute_command(&mut self, command: &str, working_dir: Option<&Path>) -> Result<(i32, String)> {
         let command = command.trim();
-        // Try with fallback strategies first for simple commands
-        if !command.contains("&&") && !command.contains("|") {
-            return self.execute_with_fallbacks(command, working_dir);
+        self.check_run_permission(command)?;
+
+        // `&&`/`;`/`|` compound commands are run as a classified pipeline so
+        // each leaf command still benefits from fallback strategies and caching.
+        if command.contains("&&") || command.contains(';') || command.contains('|') {
+            return self.execute_pipeline(command, working_dir);
         }
-        
-        self.execute_command_internal(command, working_dir)
+
+        self.execute_with_fallbacks(command, working_dir)
     }
-    
+
+    /// Checks every program `command` would invoke against the `run`
+    /// capability, prompting interactively for any that aren't covered by
+    /// the allow/deny lists. Returns an error (instead of ever silently
+    /// running) the moment one is explicitly denied or the user declines.
+    fn check_run_permission(&mut self, command: &str) -> Result<()> {
+        // A simple command is alias-expanded before it runs, so check what it
+        // expands to rather than the alias name.
+        let is_compound = command.contains("&&") || command.contains(';') || command.contains('|');
+        let effective = if is_compound { command.to_string() } else { self.expand_alias(command) };
+        for program in extract_invoked_programs(&effective) {
+            match self.permissions.check_run(&program) {
+                PermissionCheck::Allowed => {}
+                PermissionCheck::Denied => {
+                    return Err(anyhow!("Permission denied: '{}' is not allowed to run (deny-run)", program));
+                }
+                PermissionCheck::Undecided => {
+                    if !self.prompt_permission(Capability::Run, &program) {
+                        return Err(anyhow!("Permission denied: user declined to run '{}'", program));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Interactively asks the user to resolve an `Undecided` capability
+    /// request, offering "allow once / allow always / deny". "Allow always"
+    /// mutates `self.permissions` so the rest of the session no longer
+    /// prompts for this exact value.
+    fn prompt_permission(&mut self, capability: Capability, value: &str) -> bool {
+        println!(
+            "{}",
+            STYLER.error_style(format!("Permission requested: {} access to '{}'", capability.label(), value))
+        );
+        print!("Allow? (y=once / a=always / N=deny): ");
+        if io::stdout().flush().is_err() {
+            return false;
+        }
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer).is_err() {
+            return false;
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "a" | "always" => {
+                self.permissions.grant(capability, value.to_string());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reads `path` (optionally limited to a line range), checking it against
+    /// the `read` capability first. Truncates whole-file reads larger than
+    /// `MAX_FILE_READ_BYTES` to that size and `MAX_FILE_READ_LINES` lines,
+    /// reporting such a truncated read as a placeholder when it looks binary.
+    /// A file within the size limit is read whole and must be valid UTF-8.
+    pub fn read_file_to_string_with_limit(&mut self, path: &Path, line_range: Option<(usize, usize)>) -> Result<(String, bool)> {
+        self.check_path_permission(Capability::Read, path)?;
+        read_file_checked(path, line_range)
+    }
+
+    /// Like `check_path_permission(Capability::Read, ..)`, exposed so a caller
+    /// (e.g. `PrimeSession`'s parallel read dispatch) can resolve a batch of
+    /// "ask me before" prompts one at a time on the main thread before handing
+    /// the now-permitted paths to worker threads via `read_file_checked`.
+    pub fn ensure_read_permission(&mut self, path: &Path) -> Result<()> {
+        self.check_path_permission(Capability::Read, path)
+    }
+}
+
+/// The permission-checked body of `read_file_to_string_with_limit`, split out
+/// so it can also run on a worker thread once the caller has already resolved
+/// `Capability::Read` for `path` via `ensure_read_permission`.
+pub(crate) fn read_file_checked(path: &Path, line_range: Option<(usize, usize)>) -> Result<(String, bool)> {
+    let file = fs::File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
+    let reader = BufReader::new(file);
+    let mut truncated = false;
+    let content: String;
+
+    if let Some((start, end)) = line_range {
+        if start == 0 || start > end {
+            return Err(anyhow!("Invalid line range: start must be >= 1 and start <= end. Got start={} end={}", start, end));
+        }
+        let all_lines: Vec<_> = reader
+            .lines()
+            .enumerate()
+            .map(|(i, l)| {
+                l.with_context(|| format!("Failed to read line {} from file: {}", i + 1, path.display()))
+                    .unwrap_or_else(|e| {
+                        eprintln!("Warning: {}", e);
+                        String::new()
+                    })
+            })
+            .collect();
+        let total_lines = all_lines.len();
+
+        if start > total_lines {
+            content = String::new();
+            truncated = end < total_lines;
+        } else {
+            let effective_end = std::cmp::min(end, total_lines);
+            content = all_lines
+                .iter()
+                .skip(start.saturating_sub(1))
+                .take(effective_end - start.saturating_sub(1))
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n");
+            truncated = end < total_lines;
+        }
+    } else {
+        let metadata = fs::metadata(path)?;
+        if metadata.len() > MAX_FILE_READ_BYTES {
+            let mut limited_reader = BufReader::new(
+                fs::File::open(path).with_context(|| format!("Failed to open file for reading (size limit): {}", path.display()))?,
+            )
+            .take(MAX_FILE_READ_BYTES);
+            let mut buffer = Vec::new();
+            limited_reader
+                .read_to_end(&mut buffer)
+                .with_context(|| format!("Failed to read file content (size limit): {}", path.display()))?;
+
+            truncated = true;
+            if looks_binary(&buffer) {
+                content = "[binary data omitted]".into();
+            } else {
+                let text = String::from_utf8_lossy(&buffer);
+                let lines: Vec<&str> = text.lines().take(MAX_FILE_READ_LINES).collect();
+                content = lines.join("\n");
+            }
+        } else {
+            let mut tmp = String::new();
+            BufReader::new(fs::File::open(path).with_context(|| format!("Failed to open file for reading: {}", path.display()))?)
+                .read_to_string(&mut tmp)
+                .with_context(|| format!("Failed to read file content: {}", path.display()))?;
+            content = tmp;
+            truncated = false;
+        }
+    }
+
+    let mut final_content = content;
+    if truncated {
+        final_content.push_str("\n... (file content truncated)");
+    }
+
+    Ok((final_content, truncated))
+}
+
+impl CommandProcessor {
+    /// Writes `content` to `path` (overwriting unless `append`), checking it
+    /// against the `write` capability first. Creates missing parent
+    /// directories, matching how `create_tool` writes into `./prime/`.
+    pub fn write_file_to_path(&mut self, path: &Path, content: &str, append: bool) -> Result<()> {
+        self.check_path_permission(Capability::Write, path)?;
+
+        if let Some(parent) = path.parent() {
+            if !parent.exists() {
+                fs::create_dir_all(parent).with_context(|| format!("Failed to create directories for: {}", path.display()))?;
+            }
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .with_context(|| format!("Failed to open file for writing: {}", path.display()))?;
+
+        file.write_all(content.as_bytes())
+            .with_context(|| format!("Failed to write to file: {}", path.display()))
+    }
+
+    /// Lists `path`'s children, hiding anything matched by the configured
+    /// ignored-path patterns (`node_modules/`, `.git/`, etc.) and capping the
+    /// display so a huge directory doesn't flood the LLM's context.
+    pub fn list_directory_smart(&self, path: &Path) -> Result<Vec<String>> {
+        list_directory_checked(path, &self.ignored_path_patterns)
+    }
+}
+
+/// The body of `list_directory_smart`, taking the `ignored_path_patterns`
+/// slice instead of `&self` so a worker thread can list a directory with a
+/// borrow of just those patterns rather than of the whole `CommandProcessor`
+/// (see `ignored_path_patterns()`).
+pub(crate) fn list_directory_checked(path: &Path, ignored_path_patterns: &[Pattern]) -> Result<Vec<String>> {
+    if !path.is_dir() {
+        return Err(anyhow!("Path is not a directory: {}", path.display()));
+    }
+
+    let entries = fs::read_dir(path).with_context(|| format!("Failed to read directory: {}", path.display()))?;
+
+    let mut items = Vec::new();
+    for entry_result in entries {
+        let entry = entry_result.with_context(|| format!("Error reading directory entry in {}", path.display()))?;
+        let entry_path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        if ignored_path_patterns.iter().any(|p| p.matches_path(&entry_path) || p.matches(&file_name)) {
+            continue;
+        }
+
+        let display_name = if entry_path.is_dir() { format!("{}/", file_name) } else { file_name };
+        items.push(display_name);
+    }
+
+    items.sort_by(|a, b| {
+        let a_is_dir = a.ends_with('/');
+        let b_is_dir = b.ends_with('/');
+        if a_is_dir != b_is_dir {
+            b_is_dir.cmp(&a_is_dir)
+        } else {
+            a.to_lowercase().cmp(&b.to_lowercase())
+        }
+    });
+
+    if items.len() > MAX_DIR_LISTING_CHILDREN_DISPLAY {
+        let remaining = items.len() - MAX_DIR_LISTING_CHILDREN_DISPLAY;
+        let mut truncated_items = items.into_iter().take(MAX_DIR_LISTING_CHILDREN_DISPLAY).collect::<Vec<_>>();
+        truncated_items.push(format!("... (and {} more items)", remaining));
+        Ok(truncated_items)
+    } else {
+        Ok(items)
+    }
+}
+
+impl CommandProcessor {
+    /// Checks `path` against the `read`/`write` capability, prompting
+    /// interactively when it isn't covered by the allow/deny lists. Shared by
+    /// `read_file_to_string_with_limit` and `write_file_to_path`.
+    fn check_path_permission(&mut self, capability: Capability, path: &Path) -> Result<()> {
+        let path_str = path.to_string_lossy().to_string();
+        match self.permissions.check_path(capability, &path_str) {
+            PermissionCheck::Allowed => Ok(()),
+            PermissionCheck::Denied => Err(anyhow!(
+                "Permission denied: {} access to '{}' is not allowed (deny-{})",
+                capability.label(),
+                path_str,
+                capability.label()
+            )),
+            PermissionCheck::Undecided => {
+                if self.prompt_permission(capability, &path_str) {
+                    Ok(())
+                } else {
+                    Err(anyhow!("Permission denied: user declined {} access to '{}'", capability.label(), path_str))
+                }
+            }
+        }
+    }
+
+    /// Like `execute_command`, but runs it elevated (`sudo`/`doas` on Unix, a
+    /// UAC relaunch on Windows) after the user has picked "elevate" at an
+    /// `is_ask_me_before_command` prompt. Skips fallback strategies and the
+    /// result cache since an elevated run is a deliberate one-off.
+    pub fn execute_command_elevated(&mut self, command: &str, working_dir: Option<&Path>) -> Result<(i32, String)> {
+        self.execute_command_internal(command.trim(), working_dir, true)
+    }
+
+    /// The ignored-path globs (`node_modules/`, `.git/`, etc.) this processor
+    /// already loaded for `list_directory_smart`. Exposed so other
+    /// subsystems — e.g. `WatchSession` — can honor the same ignore list
+    /// instead of duplicating it.
+    pub fn ignored_path_patterns(&self) -> &[Pattern] {
+        &self.ignored_path_patterns
+    }
+
+    /// Spawns `command` through the configured shell with piped
+    /// stdout/stderr and hands back the raw `Child` instead of waiting on
+    /// it, for callers (like `WatchSession`) that need to hold a handle so
+    /// they can kill a still-running invocation before starting the next
+    /// one. Unlike `execute_command`, this does not check permissions, use
+    /// the result cache, or stream output anywhere — that's the caller's job.
+    pub fn spawn_shell(&self, command: &str, working_dir: &Path) -> Result<std::process::Child> {
+        let mut args = self.shell_args.clone();
+        args.push(command.to_string());
+        ShellCommand::new(&self.shell_command, working_dir)
+            .args(args)
+            .envs(self.env_vars.clone())
+            .spawn()
+            .with_context(|| format!("Failed to spawn command: {}", command))
+    }
+
     // Periodically clean expired cache entries
     pub fn maintain_cache(&mut self) {
         self.command_cache.clear_expired();
     }
+
+    /// Runs a lightweight probe command (e.g. a `--version` check) and returns its
+    /// trimmed output on success, `None` on failure. Unlike `execute_command`, this
+    /// never prints progress/output to the terminal and goes through the same
+    /// `CommandCache` so repeated startup-time detection stays cheap.
+    pub fn check_command(&mut self, command: &str) -> Option<String> {
+        if let Some((code, output)) = self.command_cache.get(command) {
+            return (code == 0).then(|| output.trim().to_string());
+        }
+
+        let mut args = self.shell_args.clone();
+        args.push(command.to_string());
+        let output = Command::new(&self.shell_command)
+            .args(&args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .ok()?;
+
+        let exit_code = output.status.code().unwrap_or(-1);
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let combined = if stdout.trim().is_empty() { stderr } else { stdout };
+
+        self.command_cache.set(command.to_string(), (exit_code, combined.clone()));
+        (exit_code == 0).then(|| combined.trim().to_string())
+    }
 }
 
 #[cfg(test)]
@@ -262,4 +1610,79 @@ mod tests {
         processor.maintain_cache();
         assert!(processor.command_cache.get("test").is_none());
     }
+
+    fn parse_rules(rules: &[&str]) -> PermissionSet {
+        let owned: Vec<String> = rules.iter().map(|s| s.to_string()).collect();
+        PermissionSet::parse(&owned, std::iter::empty())
+    }
+
+    #[test]
+    fn test_permission_set_parse_from_config_and_cli() {
+        let config_rules = vec!["allow-run=git,npm".to_string()];
+        let cli_args = vec!["--allow-read=./src/**", "--deny-write=/etc/**"];
+        let permissions = PermissionSet::parse(&config_rules, cli_args.into_iter());
+
+        assert_eq!(permissions.check_run("git"), PermissionCheck::Allowed);
+        assert_eq!(permissions.check_run("curl"), PermissionCheck::Undecided);
+        assert_eq!(permissions.check_path(Capability::Read, "./src/main.rs"), PermissionCheck::Allowed);
+        assert_eq!(permissions.check_path(Capability::Write, "/etc/passwd"), PermissionCheck::Denied);
+    }
+
+    #[test]
+    fn test_permission_set_allow_all_flag() {
+        let permissions = parse_rules(&["allow-all"]);
+        assert_eq!(permissions.check_run("anything"), PermissionCheck::Allowed);
+        assert_eq!(permissions.check_path(Capability::Read, "/any/path"), PermissionCheck::Allowed);
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow() {
+        let permissions = parse_rules(&["allow-run=git", "deny-run=git"]);
+        assert_eq!(permissions.check_run("git"), PermissionCheck::Denied);
+    }
+
+    #[test]
+    fn test_deny_wins_over_allow_all() {
+        let permissions = parse_rules(&["allow-all", "deny-write=/etc/**"]);
+        assert_eq!(permissions.check_path(Capability::Write, "/etc/passwd"), PermissionCheck::Denied);
+        // allow_all still covers everything else.
+        assert_eq!(permissions.check_path(Capability::Write, "/home/user/file"), PermissionCheck::Allowed);
+    }
+
+    #[test]
+    fn test_allow_all_only_upgrades_undecided() {
+        let permissions = parse_rules(&["allow-all", "deny-run=rm"]);
+        // Explicitly denied stays denied even under allow_all.
+        assert_eq!(permissions.check_run("rm"), PermissionCheck::Denied);
+        // An explicit allow still reports Allowed (not just upgraded).
+        let permissions = parse_rules(&["allow-run=git"]);
+        assert_eq!(permissions.check_run("git"), PermissionCheck::Allowed);
+    }
+
+    #[test]
+    fn test_check_path_glob_matching() {
+        let permissions = parse_rules(&["allow-read=./src/**"]);
+        assert_eq!(permissions.check_path(Capability::Read, "./src/commands/processor.rs"), PermissionCheck::Allowed);
+        assert_eq!(permissions.check_path(Capability::Read, "./other/file.rs"), PermissionCheck::Undecided);
+    }
+
+    #[test]
+    fn test_check_path_falls_back_to_substring_match() {
+        // "node_modules" isn't a glob pattern with special characters, but it
+        // should still match via the substring fallback.
+        let permissions = parse_rules(&["deny-read=node_modules"]);
+        assert_eq!(permissions.check_path(Capability::Read, "./project/node_modules/pkg/index.js"), PermissionCheck::Denied);
+    }
+
+    #[test]
+    fn test_extract_invoked_programs_simple() {
+        let programs = extract_invoked_programs("git status && npm install; ls | grep foo");
+        assert_eq!(programs, vec!["git", "npm", "ls", "grep"]);
+    }
+
+    #[test]
+    fn test_extract_invoked_programs_command_substitution() {
+        let programs = extract_invoked_programs("echo $(rm -rf /)");
+        assert_eq!(programs, vec!["echo", "rm"]);
+    }
 }
\ No newline at end of file
@@ -3,6 +3,7 @@ use crossterm::style::Stylize;
 use glob::Pattern;
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     fs,
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
@@ -32,38 +33,171 @@ pub const DEFAULT_ASK_ME_BEFORE_PATTERNS: &[&str] = &[
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
-    #[serde(default = "default_provider")]
-    pub provider: String,
     #[serde(default)]
-    pub model: Option<String>,
-    #[serde(default = "default_temperature")]
-    pub temperature: f32,
-    #[serde(default = "default_max_tokens")]
-    pub max_tokens: u32,
-    #[serde(default = "default_api_key")]
-    pub gemini_api_key: String,
-    #[serde(default = "default_api_key")]
-    pub ollama_api_key: String,
+    pub provider: ProviderSection,
+    #[serde(default)]
+    pub generation: GenerationSection,
+    #[serde(default)]
+    pub safety: SafetySection,
+    #[serde(default)]
+    pub debug: DebugSection,
+    #[serde(default)]
+    pub theme: ThemeConfig,
 }
 
-fn default_provider() -> String { "google".to_string() }
+fn default_active_provider() -> String { "google".to_string() }
 fn default_temperature() -> f32 { 0.2 }
 fn default_max_tokens() -> u32 { 8192 } // Increased for more complex plans
 fn default_api_key() -> String { "".to_string() }
+fn default_log_level() -> String { "info".to_string() }
+
+fn default_provider_profiles() -> BTreeMap<String, ProviderProfile> {
+    let mut profiles = BTreeMap::new();
+    profiles.insert(
+        "google".to_string(),
+        ProviderProfile {
+            backend: "google".to_string(),
+            api_key: default_api_key(),
+            model: None,
+            base_url: None,
+        },
+    );
+    profiles.insert(
+        "ollama".to_string(),
+        ProviderProfile {
+            backend: "ollama".to_string(),
+            api_key: default_api_key(),
+            model: None,
+            base_url: None,
+        },
+    );
+    profiles
+}
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            provider: default_provider(),
-            model: None,
+            provider: ProviderSection::default(),
+            generation: GenerationSection::default(),
+            safety: SafetySection::default(),
+            debug: DebugSection::default(),
+            theme: ThemeConfig::default(),
+        }
+    }
+}
+
+/// `[provider]` section: a registry of named profiles plus a selector for
+/// which one is active. Each profile owns its own backend kind, credentials,
+/// default model and optional base URL, so adding a new provider (or a second
+/// profile for an existing one, e.g. a local and a hosted Ollama) is a
+/// config-only change rather than a code change.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProviderSection {
+    #[serde(default = "default_active_provider")]
+    pub active: String,
+    #[serde(default = "default_provider_profiles")]
+    pub profiles: BTreeMap<String, ProviderProfile>,
+}
+
+impl Default for ProviderSection {
+    fn default() -> Self {
+        Self {
+            active: default_active_provider(),
+            profiles: default_provider_profiles(),
+        }
+    }
+}
+
+/// One entry of the `[provider]` registry, e.g. `[provider.profiles.google]`.
+/// `backend` selects which `LLMBackend` to build (see `main::resolve_backend`);
+/// `api_key` is the config-file fallback for the `<PROFILE>_API_KEY`
+/// environment variable.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProviderProfile {
+    pub backend: String,
+    #[serde(default = "default_api_key")]
+    pub api_key: String,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+/// `[generation]` section: sampling parameters sent with every request.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GenerationSection {
+    #[serde(default = "default_temperature")]
+    pub temperature: f32,
+    #[serde(default = "default_max_tokens")]
+    pub max_tokens: u32,
+}
+
+impl Default for GenerationSection {
+    fn default() -> Self {
+        Self {
             temperature: default_temperature(),
             max_tokens: default_max_tokens(),
-            gemini_api_key: default_api_key(),
-            ollama_api_key: default_api_key(),
         }
     }
 }
 
+/// `[safety]` section, holding an `ask_me_before_patterns` list inline in
+/// `config.toml`. The list is deserialized but not read by the danger guard,
+/// which is built only from the built-in defaults and the separate on-disk
+/// `ask_me_before_patterns.txt`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SafetySection {
+    #[serde(default)]
+    pub ask_me_before_patterns: Vec<String>,
+}
+
+/// `[debug]` section: diagnostics knobs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DebugSection {
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    #[serde(default)]
+    pub print_events: bool,
+}
+
+impl Default for DebugSection {
+    fn default() -> Self {
+        Self {
+            log_level: default_log_level(),
+            print_events: false,
+        }
+    }
+}
+
+/// `[theme]` config section. `preset` picks one of the built-in palettes
+/// ("dark", "light", "mono"); leaving it unset lets the `Styler` auto-detect
+/// the terminal background. Any per-role color set here (by name, e.g.
+/// "cyan", or hex, e.g. "#89b4fa") overrides that single role on top of the
+/// chosen preset.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: Option<String>,
+    #[serde(default)]
+    pub success: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub warning: Option<String>,
+    #[serde(default)]
+    pub info: Option<String>,
+    #[serde(default)]
+    pub command_exec: Option<String>,
+    #[serde(default)]
+    pub llm_response: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub separator: Option<String>,
+    #[serde(default)]
+    pub header: Option<String>,
+}
+
 pub fn load_config() -> Result<Config> {
     let config_dir = get_prime_config_dir()?;
     let config_path = config_dir.join(CONFIG_FILENAME);
@@ -96,17 +230,131 @@ pub fn load_config() -> Result<Config> {
 
     let toml_content = fs::read_to_string(&config_path)
         .with_context(|| format!("Failed to read config file from {}", config_path.display()))?;
-    
-    let config: Config = toml::from_str(&toml_content)
+
+    let mut raw: toml::Value = toml::from_str(&toml_content)
+        .with_context(|| format!("Failed to parse config file at {}", config_path.display()))?;
+    migrate_legacy_flat_keys(&mut raw);
+
+    let config: Config = raw
+        .try_into()
         .with_context(|| format!("Failed to parse config file at {}", config_path.display()))?;
 
     Ok(config)
 }
 
+/// Older `config.toml` files had `provider`/`model`/`gemini_api_key`/
+/// `ollama_api_key`/`temperature`/`max_tokens` at the top level. Hoists any of
+/// those into the new `[provider]`/`[generation]` tables (without overwriting
+/// an explicit nested value), then `migrate_single_profile_provider` carries a
+/// once-nested-but-not-yet-a-registry `[provider]` table the rest of the way,
+/// so pre-existing config files keep loading.
+fn migrate_legacy_flat_keys(raw: &mut toml::Value) {
+    let Some(table) = raw.as_table_mut() else { return };
+
+    // The legacy flat `provider` key collides with the new `[provider]` table
+    // name, so it needs its own rename rather than a plain move.
+    if let Some(legacy_name) = table.get("provider").filter(|v| !v.is_table()).cloned() {
+        table.remove("provider");
+        let section = table
+            .entry("provider")
+            .or_insert_with(|| toml::Value::Table(Default::default()));
+        if let Some(section_table) = section.as_table_mut() {
+            section_table.entry("name").or_insert(legacy_name);
+        }
+    }
+
+    for key in ["model", "gemini_api_key", "ollama_api_key"] {
+        if let Some(value) = table.remove(key) {
+            let section = table
+                .entry("provider")
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+            if let Some(section_table) = section.as_table_mut() {
+                section_table.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    for key in ["temperature", "max_tokens"] {
+        if let Some(value) = table.remove(key) {
+            let section = table
+                .entry("generation")
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+            if let Some(section_table) = section.as_table_mut() {
+                section_table.entry(key).or_insert(value);
+            }
+        }
+    }
+
+    migrate_single_profile_provider(table);
+}
+
+/// Before the `[provider]` registry existed, `[provider]` held a single
+/// active backend directly: `name`, `model`, `gemini_api_key`,
+/// `ollama_api_key`. Detects that shape (presence of `name` alongside the
+/// absence of `active`/`profiles`) and rewrites it into
+/// `active = name` plus a `profiles.<name>` entry carrying the matching
+/// api key and model, so the one profile a user already had configured
+/// keeps working without edits.
+fn migrate_single_profile_provider(table: &mut toml::map::Map<String, toml::Value>) {
+    let Some(provider) = table.get_mut("provider").and_then(|v| v.as_table_mut()) else { return };
+    if provider.contains_key("active") || provider.contains_key("profiles") {
+        return;
+    }
+    let Some(name) = provider.remove("name").and_then(|v| v.as_str().map(str::to_string)) else {
+        return;
+    };
+
+    let model = provider.remove("model");
+    let gemini_api_key = provider.remove("gemini_api_key");
+    let ollama_api_key = provider.remove("ollama_api_key");
+
+    let mut profile = toml::map::Map::new();
+    profile.insert("backend".to_string(), toml::Value::String(name.clone()));
+    let legacy_key = match name.as_str() {
+        "google" => gemini_api_key,
+        "ollama" => ollama_api_key,
+        _ => None,
+    };
+    if let Some(api_key) = legacy_key {
+        profile.insert("api_key".to_string(), api_key);
+    }
+    if let Some(model) = model {
+        profile.insert("model".to_string(), model);
+    }
+
+    let mut profiles = toml::map::Map::new();
+    profiles.insert(name.clone(), toml::Value::Table(profile));
+
+    provider.insert("active".to_string(), toml::Value::String(name));
+    provider.insert("profiles".to_string(), toml::Value::Table(profiles));
+}
+
+/// Resolves the platform-native config directory via the `directories` crate
+/// (honoring `XDG_CONFIG_HOME` on Linux, `~/Library/Application Support` on
+/// macOS, `%APPDATA%` on Windows), migrating a pre-existing `~/.prime` into it
+/// the first time this runs.
 fn get_prime_config_dir() -> Result<PathBuf> {
-    dirs::home_dir()
-        .ok_or_else(|| anyhow!("Could not determine home directory"))
-        .map(|home| home.join(".prime"))
+    let project_dirs = directories::ProjectDirs::from("", "", "prime")
+        .ok_or_else(|| anyhow!("Could not determine platform config directory"))?;
+    let config_dir = project_dirs.config_dir().to_path_buf();
+
+    if let Some(legacy_dir) = dirs::home_dir().map(|home| home.join(".prime")) {
+        if legacy_dir.exists() && legacy_dir != config_dir && !config_dir.exists() {
+            if let Some(parent) = config_dir.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Err(e) = fs::rename(&legacy_dir, &config_dir) {
+                eprintln!(
+                    "Warning: Failed to migrate legacy config directory {} to {}: {}",
+                    legacy_dir.display(),
+                    config_dir.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    Ok(config_dir)
 }
 
 fn load_patterns_from_file(
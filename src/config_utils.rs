@@ -3,12 +3,16 @@
 
 use anyhow::{Context, Result};
 use std::{
+    collections::BTreeMap,
     fs,
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
 };
 
 const ASK_ME_BEFORE_PATTERNS_FILENAME: &str = "ask_me_before_patterns.txt";
+const ALIASES_FILENAME: &str = "aliases.toml";
+const ENV_VARS_FILENAME: &str = "env.toml";
+const PERMISSIONS_FILENAME: &str = "permissions.txt";
 
 #[cfg(target_os = "windows")]
 pub const DEFAULT_ASK_ME_BEFORE_PATTERNS: &[&str] = &[
@@ -39,6 +43,130 @@ pub const DEFAULT_ASK_ME_BEFORE_PATTERNS: &[&str] = &[
     // Add more Unix-specific patterns as needed
 ];
 
+/// Default patterns for a POSIX-family shell (bash, zsh, sh, dash, fish).
+const DEFAULT_PATTERNS_POSIX: &[&str] = &[
+    "rm -rf",
+    "rm -r",
+    "mkfs",
+    "fdisk",
+    "format",
+    "dd if=",
+    "shred",
+    ":(){:|:&};:", // Fork bomb
+    "chmod -R 777",
+    "mv /* /dev/null",
+];
+
+/// Default patterns for PowerShell (Windows PowerShell or PowerShell Core).
+const DEFAULT_PATTERNS_POWERSHELL: &[&str] = &[
+    "remove-item -recurse",
+    "remove-item -force",
+    "clear-disk",
+    "initialize-disk",
+    "remove-partition",
+    "format-volume",
+    "diskpart",
+];
+
+/// Default patterns for `cmd.exe`.
+const DEFAULT_PATTERNS_CMD: &[&str] = &[
+    "rmdir /s",
+    "del /s",
+    "format",
+    "fdisk",
+    "diskpart",
+];
+
+/// The shell family a command is about to run under. Chosen at runtime (see
+/// `Shell::detect`) rather than baked in via `#[cfg(target_os)]`, so e.g. a
+/// Windows binary driving WSL bash, or a Unix build driving PowerShell Core,
+/// still guards the command family it's actually about to execute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Posix,
+    PowerShell,
+    Cmd,
+}
+
+impl Shell {
+    /// Detects the active shell, checked in priority order: an explicit
+    /// `PRIME_SHELL` override, the parent process's name, `$ComSpec`, then
+    /// `$SHELL`. Falls back to the OS's conventional default shell when none
+    /// of those are recognized.
+    pub fn detect() -> Self {
+        if let Ok(over) = std::env::var("PRIME_SHELL") {
+            if let Some(shell) = Self::from_name(&over) {
+                return shell;
+            }
+        }
+        if let Some(name) = parent_process_name() {
+            if let Some(shell) = Self::from_name(&name) {
+                return shell;
+            }
+        }
+        if let Ok(comspec) = std::env::var("ComSpec") {
+            if let Some(shell) = Self::from_name(&comspec) {
+                return shell;
+            }
+        }
+        if let Ok(shell_var) = std::env::var("SHELL") {
+            if let Some(shell) = Self::from_name(&shell_var) {
+                return shell;
+            }
+        }
+        if cfg!(target_os = "windows") {
+            Shell::Cmd
+        } else {
+            Shell::Posix
+        }
+    }
+
+    /// Maps a path or process name (e.g. `/bin/zsh`, `powershell.exe`) to the
+    /// shell family it belongs to, ignoring any directory or extension.
+    fn from_name(value: &str) -> Option<Self> {
+        // Split on both separators so a Windows path (e.g. `$ComSpec`) is
+        // reduced to its file name on any host.
+        let file_name = value.rsplit(|c| c == '/' || c == '\\').next()?;
+        let stem = Path::new(file_name).file_stem()?.to_str()?.to_lowercase();
+        match stem.as_str() {
+            "bash" | "zsh" | "sh" | "dash" | "fish" => Some(Shell::Posix),
+            "powershell" | "pwsh" => Some(Shell::PowerShell),
+            "cmd" => Some(Shell::Cmd),
+            _ => None,
+        }
+    }
+
+    /// Returns the built-in default "ask me before" patterns for this shell.
+    fn default_patterns(self) -> &'static [&'static str] {
+        match self {
+            Shell::Posix => DEFAULT_PATTERNS_POSIX,
+            Shell::PowerShell => DEFAULT_PATTERNS_POWERSHELL,
+            Shell::Cmd => DEFAULT_PATTERNS_CMD,
+        }
+    }
+}
+
+/// Reads the parent process's executable name from `/proc` on Linux. Returns
+/// `None` on platforms without a `/proc` (including macOS), where the parent
+/// shell falls back to the `$ComSpec`/`$SHELL` checks in `Shell::detect`.
+#[cfg(target_os = "linux")]
+fn parent_process_name() -> Option<String> {
+    let ppid = fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("PPid:"))?
+        .trim()
+        .to_string();
+    fs::read_to_string(format!("/proc/{}/comm", ppid))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn parent_process_name() -> Option<String> {
+    None
+}
+
 /// Returns the path to the Prime configuration directory (e.g., ~/.prime/).
 fn get_prime_config_dir() -> Result<PathBuf> {
     dirs::home_dir()
@@ -46,13 +174,41 @@ fn get_prime_config_dir() -> Result<PathBuf> {
         .map(|home| home.join(".prime"))
 }
 
-/// Loads patterns from a given file in the Prime config directory.
-/// If the file doesn't exist or is empty, returns the provided default patterns.
+/// Loads patterns from a given file in the Prime config directory, each
+/// paired with its 1-based line number so a compile error (see
+/// `danger_guard::PatternSet::compile`) can be reported against the
+/// original file. If the file doesn't exist or is empty, returns the
+/// provided default patterns instead, each tagged with line `0` since they
+/// don't come from a file.
 fn load_patterns_from_file(
     config_dir: &Path,
     filename: &str,
     default_patterns: &[&str],
-) -> Result<Vec<String>> {
+) -> Result<Vec<(usize, String)>> {
+    let mut patterns = read_pattern_file(config_dir, filename)?;
+
+    if patterns.is_empty() {
+        patterns = default_patterns
+            .iter()
+            .map(|s| (0, s.to_string()))
+            .collect();
+        if !config_dir.exists() {
+            fs::create_dir_all(config_dir).with_context(|| {
+                format!(
+                    "Failed to create Prime config directory: {}",
+                    config_dir.display()
+                )
+            })?;
+        }
+    }
+    Ok(patterns)
+}
+
+/// Reads whatever patterns are present in `config_dir/filename`, each paired
+/// with its 1-based line number. Unlike `load_patterns_from_file`, this never
+/// falls back to defaults — it simply returns an empty `Vec` when the file
+/// doesn't exist, so callers can merge it with defaults of their own choosing.
+fn read_pattern_file(config_dir: &Path, filename: &str) -> Result<Vec<(usize, String)>> {
     let file_path = config_dir.join(filename);
     let mut patterns = Vec::new();
 
@@ -60,7 +216,7 @@ fn load_patterns_from_file(
         let file = fs::File::open(&file_path)
             .with_context(|| format!("Failed to open pattern file: {}", file_path.display()))?;
         let reader = BufReader::new(file);
-        for line in reader.lines() {
+        for (idx, line) in reader.lines().enumerate() {
             let line_content = line.with_context(|| {
                 format!(
                     "Failed to read line from pattern file: {}",
@@ -69,30 +225,17 @@ fn load_patterns_from_file(
             })?;
             let trimmed_line = line_content.trim();
             if !trimmed_line.is_empty() && !trimmed_line.starts_with('#') {
-                patterns.push(trimmed_line.to_string());
+                patterns.push((idx + 1, trimmed_line.to_string()));
             }
         }
     }
-
-    if patterns.is_empty() {
-        patterns = default_patterns
-            .iter()
-            .map(|s| s.to_string())
-            .collect();
-        if !config_dir.exists() {
-            fs::create_dir_all(config_dir).with_context(|| {
-                format!(
-                    "Failed to create Prime config directory: {}",
-                    config_dir.display()
-                )
-            })?;
-        }
-    }
     Ok(patterns)
 }
 
-/// Loads "ask me before" (potentially destructive) command patterns (simple string contains).
-pub fn load_ask_me_before_patterns() -> Result<Vec<String>> {
+/// Loads "ask me before" (potentially destructive) command patterns, each
+/// paired with the file line it came from, ready to compile into a
+/// `danger_guard::PatternSet`.
+pub fn load_ask_me_before_patterns() -> Result<Vec<(usize, String)>> {
     let config_dir = get_prime_config_dir()?;
     load_patterns_from_file(
         &config_dir,
@@ -101,6 +244,94 @@ pub fn load_ask_me_before_patterns() -> Result<Vec<String>> {
     )
 }
 
+/// Loads "ask me before" patterns the same way as `load_ask_me_before_patterns`,
+/// but picks the built-in default bank for `shell` instead of the compile-time
+/// `#[cfg(target_os)]` split, so the guard matches the shell family a command
+/// is actually about to run under. The user's own patterns in
+/// `ask_me_before_patterns.txt` are always merged on top of the shell's
+/// defaults, rather than only as a fallback when the file is empty.
+pub fn load_ask_me_before_patterns_for_shell(shell: Shell) -> Result<Vec<(usize, String)>> {
+    let config_dir = get_prime_config_dir()?;
+    let mut patterns: Vec<(usize, String)> = shell
+        .default_patterns()
+        .iter()
+        .map(|s| (0, s.to_string()))
+        .collect();
+    patterns.extend(read_pattern_file(&config_dir, ASK_ME_BEFORE_PATTERNS_FILENAME)?);
+    Ok(patterns)
+}
+
+/// Loads a TOML file of `name = "value"` pairs from the Prime config directory,
+/// returning an empty map (rather than an error) when the file doesn't exist yet.
+fn load_toml_map(config_dir: &Path, filename: &str) -> Result<BTreeMap<String, String>> {
+    let file_path = config_dir.join(filename);
+    if !file_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = fs::read_to_string(&file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", file_path.display()))
+}
+
+/// Persists a `name = "value"` map as TOML in the Prime config directory,
+/// creating the directory if it doesn't exist yet.
+fn save_toml_map(config_dir: &Path, filename: &str, map: &BTreeMap<String, String>) -> Result<()> {
+    if !config_dir.exists() {
+        fs::create_dir_all(config_dir)
+            .with_context(|| format!("Failed to create Prime config directory: {}", config_dir.display()))?;
+    }
+    let toml_string = toml::to_string_pretty(map).context("Failed to serialize TOML map")?;
+    let file_path = config_dir.join(filename);
+    fs::write(&file_path, toml_string)
+        .with_context(|| format!("Failed to write {}", file_path.display()))
+}
+
+/// Loads user-defined command aliases from `~/.prime/aliases.toml`.
+pub fn load_aliases() -> Result<BTreeMap<String, String>> {
+    load_toml_map(&get_prime_config_dir()?, ALIASES_FILENAME)
+}
+
+/// Persists `aliases` back to `~/.prime/aliases.toml`.
+pub fn save_aliases(aliases: &BTreeMap<String, String>) -> Result<()> {
+    save_toml_map(&get_prime_config_dir()?, ALIASES_FILENAME, aliases)
+}
+
+/// Loads the persistent shell environment from `~/.prime/env.toml`.
+pub fn load_env_vars() -> Result<BTreeMap<String, String>> {
+    load_toml_map(&get_prime_config_dir()?, ENV_VARS_FILENAME)
+}
+
+/// Persists `env_vars` back to `~/.prime/env.toml`.
+pub fn save_env_vars(env_vars: &BTreeMap<String, String>) -> Result<()> {
+    save_toml_map(&get_prime_config_dir()?, ENV_VARS_FILENAME, env_vars)
+}
+
+/// Loads capability allow/deny rules from `~/.prime/permissions.txt`, one
+/// `allow-<cap>=a,b,c` / `deny-<cap>=a,b,c` / `allow-all` directive per line,
+/// the same shape as the `--allow-run=git,npm` family of CLI flags, so a
+/// project can bake in a standing grant instead of re-typing it every run.
+pub fn load_permission_rules() -> Result<Vec<String>> {
+    read_permission_rules(&get_prime_config_dir()?)
+}
+
+/// Reads whatever permission rules are present in `config_dir/permissions.txt`,
+/// returning an empty list (not an error) when the file doesn't exist yet.
+/// Split out from `load_permission_rules` so tests can point it at a temp dir.
+fn read_permission_rules(config_dir: &Path) -> Result<Vec<String>> {
+    let file_path = config_dir.join(PERMISSIONS_FILENAME);
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&file_path)
+        .with_context(|| format!("Failed to read {}", file_path.display()))?;
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,8 +366,11 @@ mod tests {
         )
         .unwrap();
         assert_eq!(patterns.len(), custom_patterns.len());
-        assert!(patterns.contains(&"dangerous-command".to_string()));
-        assert!(!patterns.contains(&"rm -rf".to_string())); // Default should not be loaded
+        assert!(patterns.iter().any(|(_, p)| p == "dangerous-command"));
+        assert!(!patterns.iter().any(|(_, p)| p == "rm -rf")); // Default should not be loaded
+        // Line numbers are 1-based and reflect position in the file.
+        assert_eq!(patterns[0], (1, "dangerous-command".to_string()));
+        assert_eq!(patterns[1], (2, "another-risky-operation".to_string()));
     }
 
     #[test]
@@ -162,8 +396,78 @@ risky-operation
         )
         .unwrap();
         assert_eq!(patterns.len(), 2);
-        assert!(patterns.contains(&"dangerous-command".to_string()));
-        assert!(patterns.contains(&"risky-operation".to_string()));
-        assert!(!patterns.iter().any(|p| p.starts_with('#')));
+        assert!(patterns.iter().any(|(_, p)| p == "dangerous-command"));
+        assert!(patterns.iter().any(|(_, p)| p == "risky-operation"));
+        assert!(!patterns.iter().any(|(_, p)| p.starts_with('#')));
+    }
+
+    #[test]
+    fn test_shell_from_name_recognizes_common_shells() {
+        assert_eq!(Shell::from_name("/bin/bash"), Some(Shell::Posix));
+        assert_eq!(Shell::from_name("/usr/bin/zsh"), Some(Shell::Posix));
+        assert_eq!(Shell::from_name("powershell.exe"), Some(Shell::PowerShell));
+        assert_eq!(Shell::from_name("pwsh"), Some(Shell::PowerShell));
+        assert_eq!(Shell::from_name(r"C:\Windows\System32\cmd.exe"), Some(Shell::Cmd));
+        assert_eq!(Shell::from_name("nu"), None);
+    }
+
+    #[test]
+    fn test_load_ask_me_before_patterns_for_shell_merges_user_patterns() {
+        let temp_config_dir = tempdir().unwrap();
+        fs::write(
+            temp_config_dir.path().join(ASK_ME_BEFORE_PATTERNS_FILENAME),
+            "my-custom-pattern\n",
+        )
+        .unwrap();
+
+        // `load_ask_me_before_patterns_for_shell` reads the home config dir, so
+        // this builds the same defaults-plus-file merge against a temp dir.
+        let mut patterns: Vec<(usize, String)> = Shell::PowerShell
+            .default_patterns()
+            .iter()
+            .map(|s| (0, s.to_string()))
+            .collect();
+        patterns.extend(read_pattern_file(temp_config_dir.path(), ASK_ME_BEFORE_PATTERNS_FILENAME).unwrap());
+
+        assert!(patterns.iter().any(|(_, p)| p == "diskpart"));
+        assert!(patterns.iter().any(|(_, p)| p == "my-custom-pattern"));
+    }
+
+    #[test]
+    fn test_load_toml_map_missing_file_returns_empty() {
+        let temp_config_dir = tempdir().unwrap();
+        let map = load_toml_map(temp_config_dir.path(), "missing.toml").unwrap();
+        assert!(map.is_empty());
+    }
+
+    #[test]
+    fn test_load_permission_rules_missing_file_returns_empty() {
+        let temp_config_dir = tempdir().unwrap();
+        assert!(read_permission_rules(temp_config_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_permission_rules_ignores_comments_and_blanks() {
+        let temp_config_dir = tempdir().unwrap();
+        fs::write(
+            temp_config_dir.path().join(PERMISSIONS_FILENAME),
+            "allow-all\n\n# grant npm run access\nallow-run=git,npm\n",
+        )
+        .unwrap();
+
+        let rules = read_permission_rules(temp_config_dir.path()).unwrap();
+        assert_eq!(rules, vec!["allow-all".to_string(), "allow-run=git,npm".to_string()]);
+    }
+
+    #[test]
+    fn test_save_and_load_toml_map_round_trips() {
+        let temp_config_dir = tempdir().unwrap();
+        let mut map = BTreeMap::new();
+        map.insert("ll".to_string(), "ls -la".to_string());
+        map.insert("gs".to_string(), "git status".to_string());
+
+        save_toml_map(temp_config_dir.path(), "aliases.toml", &map).unwrap();
+        let loaded = load_toml_map(temp_config_dir.path(), "aliases.toml").unwrap();
+        assert_eq!(loaded, map);
     }
 }
\ No newline at end of file
@@ -4,6 +4,7 @@ use std::io::{self, Write};
 use std::path::PathBuf;
 use anyhow::{Context, Result};
 use crossterm::style::Stylize;
+use glob::glob;
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
@@ -49,7 +50,8 @@ pub fn display_init_info(
 pub async fn run_repl(mut session: PrimeSession) -> Result<()> {
     let mut editor = Editor::<PrimeHelper, DefaultHistory>::new()
         .context("Failed to initialize rustyline editor")?;
-    editor.set_helper(Some(PrimeHelper {}));
+    let repl_cwd = std::rc::Rc::new(std::cell::RefCell::new(session.working_dir.clone()));
+    editor.set_helper(Some(PrimeHelper::new(repl_cwd.clone())));
    
     let prime_config_dir = dirs::home_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -82,6 +84,7 @@ pub async fn run_repl(mut session: PrimeSession) -> Result<()> {
                 if let Err(e) = session.process_input(input).await {
                     eprintln!("{}", format!("[ERROR] {}", e).red());
                 }
+                *repl_cwd.borrow_mut() = session.working_dir.clone();
             }
             Err(ReadlineError::Interrupted) => {
                 println!("\n{}", "Interrupted. Type 'exit' or Ctrl-D to exit.".yellow());
@@ -130,6 +133,11 @@ fn handle_special_command(cmd_line: &str, session: &mut PrimeSession) -> Result<
                 "!memory [long|short]".cyan()
             );
             println!(" {:<25} - List all available tools.", "!tools".cyan());
+            println!(
+                " {:<25} - List aliases, or define one.",
+                "!alias [name expansion]".cyan()
+            );
+            println!(" {:<25} - List session env vars, or set one.", "!env [KEY=VALUE]".cyan());
             println!(" {:<25} - Exit Prime.", "!exit | !quit".cyan());
             Ok(true)
         }
@@ -158,6 +166,50 @@ fn handle_special_command(cmd_line: &str, session: &mut PrimeSession) -> Result<
             println!("{}", session.list_tools());
             Ok(true)
         }
+        "alias" => {
+            if args.trim().is_empty() {
+                let aliases = session.command_processor.list_aliases();
+                if aliases.is_empty() {
+                    println!("No aliases defined. Use !alias <name> <expansion> to add one.");
+                } else {
+                    for (name, expansion) in aliases {
+                        println!(" {:<15} -> {}", name.clone().cyan(), expansion);
+                    }
+                }
+            } else {
+                let parts: Vec<&str> = args.splitn(2, ' ').collect();
+                if parts.len() < 2 {
+                    println!("{} Usage: !alias <name> <expansion>", "Error:".red());
+                } else {
+                    let (name, expansion) = (parts[0], parts[1]);
+                    match session.command_processor.set_alias(name, expansion) {
+                        Ok(()) => println!("Alias '{}' -> '{}' saved.", name, expansion),
+                        Err(e) => eprintln!("{}", format!("Failed to save alias: {}", e).red()),
+                    }
+                }
+            }
+            Ok(true)
+        }
+        "env" => {
+            if args.trim().is_empty() {
+                let env_vars = session.command_processor.list_env_vars();
+                if env_vars.is_empty() {
+                    println!("No session environment variables set. Use !env KEY=VALUE to add one.");
+                } else {
+                    for (key, value) in env_vars {
+                        println!(" {}={}", key.clone().cyan(), value);
+                    }
+                }
+            } else if let Some((key, value)) = args.split_once('=') {
+                match session.command_processor.set_env_var(key.trim(), value.trim()) {
+                    Ok(()) => println!("Environment variable '{}' saved.", key.trim()),
+                    Err(e) => eprintln!("{}", format!("Failed to save env var: {}", e).red()),
+                }
+            } else {
+                println!("{} Usage: !env KEY=VALUE", "Error:".red());
+            }
+            Ok(true)
+        }
         "exit" | "quit" => Ok(false),
         _ => {
             println!(
@@ -171,7 +223,144 @@ fn handle_special_command(cmd_line: &str, session: &mut PrimeSession) -> Result<
     }
 }
 
-pub struct PrimeHelper {}
+/// Quotes `candidate` if it contains whitespace, so a completed filename or
+/// binary name with a space in it doesn't get parsed as two shell words.
+fn quote_if_needed(candidate: &str) -> String {
+    if candidate.contains(char::is_whitespace) {
+        format!("\"{}\"", candidate)
+    } else {
+        candidate.to_string()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.is_file()
+        && std::fs::metadata(path)
+            .map(|m| m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_executable_file(path: &std::path::Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| matches!(e.to_lowercase().as_str(), "exe" | "bat" | "cmd"))
+            .unwrap_or(false)
+}
+
+pub struct PrimeHelper {
+    /// Tracks the REPL's current working directory so path completion stays
+    /// correct after a `cd`/`ChangeDir` tool call moves the session elsewhere.
+    cwd: std::rc::Rc<std::cell::RefCell<PathBuf>>,
+    /// Lazily-populated, session-lifetime cache of every executable name found
+    /// on `$PATH`, so completing the first token doesn't re-walk `$PATH` on
+    /// every keystroke.
+    path_binaries: std::cell::RefCell<Option<Vec<String>>>,
+}
+
+impl PrimeHelper {
+    fn new(cwd: std::rc::Rc<std::cell::RefCell<PathBuf>>) -> Self {
+        Self { cwd, path_binaries: std::cell::RefCell::new(None) }
+    }
+
+    /// Walks every directory on `$PATH` once per session, collecting the
+    /// names of executable entries, mirroring how a real shell builds its
+    /// command-completion table.
+    fn path_binaries(&self) -> Vec<String> {
+        if let Some(cached) = self.path_binaries.borrow().as_ref() {
+            return cached.clone();
+        }
+        let mut names = std::collections::BTreeSet::new();
+        if let Some(path_var) = env::var_os("PATH") {
+            for dir in env::split_paths(&path_var) {
+                let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    let path = entry.path();
+                    if is_executable_file(&path) {
+                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                            names.insert(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+        let binaries: Vec<String> = names.into_iter().collect();
+        *self.path_binaries.borrow_mut() = Some(binaries.clone());
+        binaries
+    }
+
+    /// Completes the first word of the line against every executable on
+    /// `$PATH`, the way a shell completes command names.
+    fn complete_binary(&self, line: &str, pos: usize) -> Result<(usize, Vec<Pair>), ReadlineError> {
+        let before_cursor = &line[..pos];
+        let word_start = before_cursor.find(|c: char| !c.is_whitespace()).unwrap_or(pos);
+        let word = &line[word_start..pos];
+
+        let candidates = self
+            .path_binaries()
+            .into_iter()
+            .filter(|name| name.starts_with(word))
+            .map(|name| Pair { display: name.clone(), replacement: quote_if_needed(&name) })
+            .collect();
+        Ok((word_start, candidates))
+    }
+
+    /// Glob-completes the whitespace-delimited word under the cursor as a filesystem
+    /// path relative to the tracked working directory, suffixing directories with `/`.
+    fn complete_path(&self, line: &str, pos: usize) -> Result<(usize, Vec<Pair>), ReadlineError> {
+        let before_cursor = &line[..pos];
+        let word_start = before_cursor
+            .rfind(char::is_whitespace)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let word = &line[word_start..pos];
+
+        let cwd = self.cwd.borrow();
+        let pattern = format!("{}*", cwd.join(word).to_string_lossy());
+
+        let mut candidates = Vec::new();
+        if let Ok(entries) = glob(&pattern) {
+            let word_dir = std::path::Path::new(word)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .filter(|s| !s.is_empty());
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let is_dir = entry.is_dir();
+                let file_name = entry
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let mut replacement = match &word_dir {
+                    Some(dir) => format!("{}/{}", dir, file_name),
+                    None => file_name.clone(),
+                };
+                if is_dir {
+                    replacement.push('/');
+                }
+                let replacement = if is_dir {
+                    // Keep the trailing `/` outside the quotes so completion can
+                    // keep chaining into the directory.
+                    let (base, slash) = replacement.split_at(replacement.len() - 1);
+                    format!("{}{}", quote_if_needed(base), slash)
+                } else {
+                    quote_if_needed(&replacement)
+                };
+
+                candidates.push(Pair {
+                    display: if is_dir { format!("{}/", file_name) } else { file_name },
+                    replacement,
+                });
+            }
+        }
+        Ok((word_start, candidates))
+    }
+}
 
 impl Helper for PrimeHelper {}
 
@@ -257,7 +446,12 @@ impl Completer for PrimeHelper {
             }
             return Ok((pos, candidates));
         }
-        Ok((0, Vec::new()))
+        let completing_first_token = !line[..pos].trim_start().contains(char::is_whitespace);
+        if completing_first_token {
+            self.complete_binary(line, pos)
+        } else {
+            self.complete_path(line, pos)
+        }
     }
 }
 
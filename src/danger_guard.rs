@@ -0,0 +1,129 @@
+//! danger_guard.rs — compiles "ask me before" patterns into a `PatternSet`:
+//! plain lines are matched literally via a single Aho-Corasick automaton,
+//! `glob:`-prefixed lines are compiled as shell-style wildcards matched
+//! against the whole command, and `re:`-prefixed lines are compiled as
+//! regexes (checked via a single `RegexSet`) — all in one pass instead of a
+//! substring scan per pattern.
+
+use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
+use glob::Pattern as GlobPattern;
+use regex::RegexSet;
+
+/// Lowercases `command` and collapses runs of whitespace to a single space,
+/// so `rm   -rf` and `RM -RF` both trip the `rm -rf` pattern.
+pub fn normalize(command: &str) -> String {
+    command.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Which matching strategy compiled a pattern, so callers can explain why a
+/// command was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    Literal,
+    Glob,
+    Regex,
+}
+
+/// One compiled "ask me before" pattern, plus its original source line so a
+/// match can be explained to the user.
+pub struct CompiledPattern {
+    pub kind: PatternKind,
+    pub source: String,
+}
+
+/// A pattern that failed to compile, annotated with the 1-based line number
+/// it came from in `ask_me_before_patterns.txt` (or `0` for a built-in
+/// default, which has no file line), so the loader can report it instead of
+/// silently dropping the pattern.
+pub struct CompileError {
+    pub line: usize,
+    pub pattern: String,
+    pub message: String,
+}
+
+/// Guards command execution against a configured list of dangerous patterns.
+/// A bare line is matched as a literal substring (case-insensitively); a
+/// `glob:`-prefixed line is compiled as a shell-style wildcard (`*`, `?`,
+/// `[...]`) matched against the whole command; a `re:`-prefixed line is
+/// compiled as a regex, so e.g. `re:dd\s+if=.*of=/dev/sd` can express shapes
+/// a literal or glob can't.
+pub struct PatternSet {
+    literals: AhoCorasick,
+    literal_patterns: Vec<CompiledPattern>,
+    globs: Vec<(GlobPattern, CompiledPattern)>,
+    regexes: RegexSet,
+    regex_patterns: Vec<CompiledPattern>,
+}
+
+impl PatternSet {
+    /// Compiles `patterns` (each paired with the 1-based source line it was
+    /// read from) once at startup. Returns the compiled set alongside any
+    /// patterns that failed to compile, rather than silently dropping them.
+    pub fn compile(patterns: &[(usize, String)]) -> (Self, Vec<CompileError>) {
+        let mut literal_patterns = Vec::new();
+        let mut glob_candidates = Vec::new();
+        let mut regex_candidates = Vec::new();
+        let mut errors = Vec::new();
+
+        for (line, pattern) in patterns {
+            if let Some(glob_src) = pattern.strip_prefix("glob:") {
+                glob_candidates.push((*line, pattern.clone(), glob_src.to_lowercase()));
+            } else if let Some(regex_src) = pattern.strip_prefix("re:") {
+                regex_candidates.push((*line, pattern.clone(), regex_src.to_string()));
+            } else {
+                literal_patterns.push(CompiledPattern { kind: PatternKind::Literal, source: pattern.to_lowercase() });
+            }
+        }
+
+        let literal_strs: Vec<&str> = literal_patterns.iter().map(|p| p.source.as_str()).collect();
+        let literals = AhoCorasickBuilder::new()
+            .ascii_case_insensitive(true)
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(&literal_strs)
+            .expect("literal danger-guard patterns failed to compile");
+
+        let mut globs = Vec::new();
+        for (line, source, glob_src) in glob_candidates {
+            match GlobPattern::new(&glob_src) {
+                Ok(compiled) => globs.push((compiled, CompiledPattern { kind: PatternKind::Glob, source })),
+                Err(e) => errors.push(CompileError { line, pattern: source, message: e.to_string() }),
+            }
+        }
+
+        let mut regex_patterns = Vec::new();
+        let mut regex_strs = Vec::new();
+        for (line, source, regex_src) in regex_candidates {
+            match regex::Regex::new(&regex_src) {
+                Ok(_) => {
+                    regex_strs.push(regex_src);
+                    regex_patterns.push(CompiledPattern { kind: PatternKind::Regex, source });
+                }
+                Err(e) => errors.push(CompileError { line, pattern: source, message: e.to_string() }),
+            }
+        }
+        let regexes = RegexSet::new(&regex_strs).unwrap_or_else(|e| {
+            errors.push(CompileError { line: 0, pattern: "re:*".to_string(), message: e.to_string() });
+            RegexSet::new(Vec::<&str>::new()).expect("empty RegexSet always compiles")
+        });
+
+        (Self { literals, literal_patterns, globs, regexes, regex_patterns }, errors)
+    }
+
+    /// Checks an already-`normalize`d command against every compiled
+    /// pattern, returning the first one that matched (literals win, then
+    /// globs, then regexes, matching the order they're checked in) so
+    /// callers know which rule fired.
+    pub fn matches(&self, normalized_cmd: &str) -> Option<&CompiledPattern> {
+        if let Some(m) = self.literals.find(normalized_cmd) {
+            return Some(&self.literal_patterns[m.pattern().as_usize()]);
+        }
+        if let Some((_, compiled)) = self.globs.iter().find(|(pat, _)| pat.matches(normalized_cmd)) {
+            return Some(compiled);
+        }
+        self.regexes
+            .matches(normalized_cmd)
+            .iter()
+            .next()
+            .map(|idx| &self.regex_patterns[idx])
+    }
+}
@@ -0,0 +1,85 @@
+//! diff.rs — git-aware diff rendering: computes a unified diff between the
+//! working-tree version of a file and its git HEAD blob (falling back to an
+//! in-memory text diff between two buffers when the file isn't tracked) and
+//! renders it through `ui::diff_panel` with per-line add/remove coloring.
+
+use std::path::Path;
+
+use crossterm::style::Stylize;
+use git2::{DiffFormat, DiffOptions, Repository};
+use similar::{ChangeTag, TextDiff};
+
+use crate::ui;
+
+/// Renders a reviewable change summary for `path`: a colorized unified diff
+/// against git HEAD when `path` sits in a tracked git repository, otherwise
+/// an in-memory diff between `old` and `new`.
+pub fn diff_panel(path: &Path, old: &str, new: &str) -> String {
+    let body = git_diff(path).unwrap_or_else(|| render_unified(old, new));
+    ui::panel(&format!("diff: {}", path.display()), &body, None)
+}
+
+/// Diffs `path`'s current on-disk contents against the repo's HEAD blob for
+/// that path. Returns `None` when `path` isn't inside a git repository, isn't
+/// tracked at HEAD, or produces an empty diff (unstaged-but-identical file).
+fn git_diff(path: &Path) -> Option<String> {
+    let repo = Repository::discover(path).ok()?;
+    let workdir = repo.workdir()?;
+    let rel_path = path.strip_prefix(workdir).unwrap_or(path);
+
+    let head_tree = repo.head().ok()?.peel_to_tree().ok()?;
+    let mut opts = DiffOptions::new();
+    opts.pathspec(rel_path);
+
+    let diff = repo
+        .diff_tree_to_workdir_with_index(Some(&head_tree), Some(&mut opts))
+        .ok()?;
+
+    let mut rendered = String::new();
+    diff.print(DiffFormat::Patch, |_delta, hunk, line| {
+        match line.origin() {
+            'F' => {} // file header: redundant with the panel title
+            'H' => {
+                if let Some(hunk) = hunk {
+                    rendered.push_str(&String::from_utf8_lossy(hunk.header()).trim_end().dim().to_string());
+                    rendered.push('\n');
+                }
+            }
+            origin => {
+                let text = String::from_utf8_lossy(line.content());
+                rendered.push_str(&render_diff_line(origin, text.trim_end_matches('\n')));
+                rendered.push('\n');
+            }
+        }
+        true
+    })
+    .ok()?;
+
+    if rendered.trim().is_empty() { None } else { Some(rendered) }
+}
+
+fn render_diff_line(origin: char, text: &str) -> String {
+    match origin {
+        '+' => format!("+{}", text).green().to_string(),
+        '-' => format!("-{}", text).red().to_string(),
+        _ => format!(" {}", text),
+    }
+}
+
+/// Line-by-line diff between `old` and `new` for files with no git history.
+fn render_unified(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let mut out = String::new();
+    for change in diff.iter_all_changes() {
+        let line = change.to_string();
+        let line = line.trim_end_matches('\n');
+        let rendered = match change.tag() {
+            ChangeTag::Delete => format!("-{}", line).red().to_string(),
+            ChangeTag::Insert => format!("+{}", line).green().to_string(),
+            ChangeTag::Equal => format!(" {}", line),
+        };
+        out.push_str(&rendered);
+        out.push('\n');
+    }
+    out
+}
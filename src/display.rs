@@ -2,9 +2,51 @@
 //! Maintains simple protocol while providing beautiful formatting
 
 use crossterm::style::Stylize;
+use crossterm::tty::IsTty;
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::time::Duration;
 
+/// Whether ANSI styling should be emitted at all, resolved once from the
+/// environment and TTY status. Mirrors the `Destination::Terminal` vs
+/// `Destination::Raw` split common to terminal-rendering libraries: every
+/// `format_*` helper below routes through this so escapes don't leak into
+/// pipes, log files, or CI output.
+static COLOR_ENABLED: Lazy<bool> = Lazy::new(Renderer::detect_color_enabled);
+
+pub struct Renderer;
+
+impl Renderer {
+    /// `NO_COLOR` (any non-empty value) always disables styling, regardless
+    /// of TTY status, per the https://no-color.org convention. `CLICOLOR_FORCE`
+    /// (any non-empty value) always enables it, even when piped. Otherwise
+    /// styling is on only when both stdout and stderr are attached to a
+    /// terminal.
+    fn detect_color_enabled() -> bool {
+        if std::env::var_os("NO_COLOR").map(|v| !v.is_empty()).unwrap_or(false) {
+            return false;
+        }
+        if std::env::var_os("CLICOLOR_FORCE").map(|v| !v.is_empty()).unwrap_or(false) {
+            return true;
+        }
+        io::stdout().is_tty() && io::stderr().is_tty()
+    }
+
+    /// Whether styling (color, bold, etc.) should be applied to output right now.
+    pub fn color_enabled() -> bool {
+        *COLOR_ENABLED
+    }
+
+    /// Whether cursor-control sequences (`clear_line`/`cursor_up`) make sense
+    /// right now. Unlike `color_enabled`, this ignores `CLICOLOR_FORCE` —
+    /// repositioning the cursor is meaningless when stdout isn't a real
+    /// terminal, even if the user forced color on for a piped log.
+    pub fn is_interactive() -> bool {
+        io::stdout().is_tty()
+    }
+}
+
 /// Display styles for different message types
 pub struct DisplayStyle {
     pub user_prefix: String,
@@ -95,7 +137,11 @@ impl ProgressBar {
 
 /// Format tool execution header
 pub fn format_tool_header(tool_name: &str, args: &str) -> String {
-    format!("┏━ {}\n┃ {}", "actions".cyan(), format!("{}: {}", tool_name, args).white())
+    let action = format!("{}: {}", tool_name, args);
+    if !Renderer::color_enabled() {
+        return format!("┏━ actions\n┃ {}", action);
+    }
+    format!("┏━ {}\n┃ {}", "actions".cyan(), action.white())
 }
 
 /// Format tool execution footer with timing
@@ -106,10 +152,12 @@ pub fn format_tool_footer(duration: Duration, success: bool) -> String {
         format!("{}ms", duration.as_millis())
     };
 
-    let status = if success {
-        format!("completed in {}", duration_str).green()
+    let status = if !Renderer::color_enabled() {
+        if success { format!("completed in {}", duration_str) } else { format!("failed after {}", duration_str) }
+    } else if success {
+        format!("completed in {}", duration_str).green().to_string()
     } else {
-        format!("failed after {}", duration_str).red()
+        format!("failed after {}", duration_str).red().to_string()
     };
 
     format!("╰────────────────────────────────────── {} ────────", status)
@@ -139,6 +187,13 @@ pub fn format_tool_output(output: &str, max_lines: Option<usize>) -> String {
 
 /// Format error message with context
 pub fn format_error(error: &str, context: Option<&str>) -> String {
+    if !Renderer::color_enabled() {
+        let mut output = format!("✗ {}", error);
+        if let Some(ctx) = context {
+            output.push_str(&format!("\n  {}", ctx));
+        }
+        return output;
+    }
     let mut output = format!("{} {}", "✗".red(), error.red());
     if let Some(ctx) = context {
         output.push_str(&format!("\n  {}", ctx.dark_grey()));
@@ -148,6 +203,9 @@ pub fn format_error(error: &str, context: Option<&str>) -> String {
 
 /// Format success message
 pub fn format_success(message: &str) -> String {
+    if !Renderer::color_enabled() {
+        return format!("✓ {}", message);
+    }
     format!("{} {}", "✓".green(), message.green())
 }
 
@@ -162,7 +220,11 @@ pub fn format_streaming_text(text: &str, width: usize) -> Vec<String> {
 /// Display a confirmation prompt
 pub fn prompt_confirmation(message: &str, default: bool) -> io::Result<bool> {
     let default_str = if default { "Y/n" } else { "y/N" };
-    print!("{} [{}]: ", message.yellow(), default_str);
+    if Renderer::color_enabled() {
+        print!("{} [{}]: ", message.yellow(), default_str);
+    } else {
+        print!("{} [{}]: ", message, default_str);
+    }
     io::stdout().flush()?;
 
     let mut input = String::new();
@@ -177,18 +239,129 @@ pub fn prompt_confirmation(message: &str, default: bool) -> io::Result<bool> {
     })
 }
 
-/// Clear current line (for updating spinners/progress)
+/// Clear current line (for updating spinners/progress). A no-op when stdout
+/// isn't a terminal, since the control sequence would otherwise land in a
+/// pipe or log file as literal garbage.
 pub fn clear_line() {
+    if !Renderer::is_interactive() {
+        return;
+    }
     print!("\r\x1b[K");
     let _ = io::stdout().flush();
 }
 
-/// Move cursor up N lines
+/// Move cursor up N lines. A no-op when stdout isn't a terminal; see `clear_line`.
 pub fn cursor_up(n: usize) {
+    if !Renderer::is_interactive() {
+        return;
+    }
     print!("\x1b[{}A", n);
     let _ = io::stdout().flush();
 }
 
+/// One live line owned by a `StatusRegion`.
+struct StatusLine {
+    text: String,
+}
+
+/// Coordinates multiple live status lines (spinner ticks, progress bars)
+/// keyed by id, rendering them as a single fixed block at the bottom of the
+/// terminal instead of letting them trample each other. Each `update` redraws
+/// only that block, via `cursor_up`/`clear_line`, leaving scrolling log
+/// output above it undisturbed. Falls back to append-only plain-line output
+/// when stdout isn't a TTY, and clears the block when dropped (or via an
+/// explicit `clear()` call, e.g. from a Ctrl-C handler).
+pub struct StatusRegion {
+    order: Vec<String>,
+    lines: HashMap<String, StatusLine>,
+    rendered_count: usize,
+    interactive: bool,
+}
+
+impl StatusRegion {
+    pub fn new() -> Self {
+        Self {
+            order: Vec::new(),
+            lines: HashMap::new(),
+            rendered_count: 0,
+            interactive: Renderer::is_interactive(),
+        }
+    }
+
+    /// How many lines are currently tracked, interactive or not.
+    pub fn line_count(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Inserts or updates the line keyed by `id` and redraws. On a non-TTY,
+    /// this just prints `text` as its own line instead of redrawing a block.
+    pub fn update(&mut self, id: &str, text: impl Into<String>) {
+        let text = text.into();
+        if !self.lines.contains_key(id) {
+            self.order.push(id.to_string());
+        }
+        self.lines.insert(id.to_string(), StatusLine { text: text.clone() });
+
+        if self.interactive {
+            self.render();
+        } else {
+            println!("{}", text);
+        }
+    }
+
+    /// Drops the line keyed by `id`, if present, and redraws.
+    pub fn remove(&mut self, id: &str) {
+        if self.lines.remove(id).is_some() {
+            self.order.retain(|existing| existing != id);
+            if self.interactive {
+                self.render();
+            }
+        }
+    }
+
+    /// Moves the cursor back up over the previously rendered block and
+    /// reprints every tracked line in order.
+    fn render(&mut self) {
+        if self.rendered_count > 0 {
+            cursor_up(self.rendered_count);
+        }
+        for id in &self.order {
+            clear_line();
+            if let Some(line) = self.lines.get(id) {
+                println!("{}", line.text);
+            }
+        }
+        self.rendered_count = self.order.len();
+    }
+
+    /// Erases the rendered block and restores the cursor to where the block
+    /// used to start. Idempotent, so it's safe to call from both `Drop` and
+    /// a signal handler.
+    pub fn clear(&mut self) {
+        if self.interactive && self.rendered_count > 0 {
+            cursor_up(self.rendered_count);
+            for _ in 0..self.rendered_count {
+                clear_line();
+                println!();
+            }
+            cursor_up(self.rendered_count);
+        }
+        self.rendered_count = 0;
+    }
+}
+
+impl Default for StatusRegion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for StatusRegion {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +384,20 @@ mod tests {
         assert!(output.contains("Processing"));
     }
 
+    #[test]
+    fn test_status_region_tracks_and_removes_lines() {
+        let mut region = StatusRegion::new();
+        region.update("spinner", "⠋ Loading");
+        region.update("progress", "[====] 50%");
+        assert_eq!(region.line_count(), 2);
+
+        region.update("spinner", "⠙ Loading");
+        assert_eq!(region.line_count(), 2);
+
+        region.remove("progress");
+        assert_eq!(region.line_count(), 1);
+    }
+
     #[test]
     fn test_text_wrapping() {
         let text = "This is a very long line that should be wrapped at the specified width";
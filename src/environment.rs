@@ -1,20 +1,72 @@
 // src/environment.rs
 // Environment detection and information
 
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::commands::CommandProcessor;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnvironmentInfo {
     pub os: String,
     pub python_version: Option<String>,
     pub pip_version: Option<String>,
     pub has_sudo: bool,
     pub in_venv: bool,
-    pub has_git: bool,
-    pub has_npm: bool,
-    pub has_docker: bool,
-    pub has_rust: bool,
     pub shell: Option<String>,
+    /// Probe label (e.g. "git", "node") -> detected version string. A `BTreeMap`
+    /// keeps key order deterministic so `fingerprint()` is stable across runs.
+    pub runtimes: BTreeMap<String, String>,
+}
+
+/// Declares how to detect one runtime/tool without touching `EnvironmentInfo`
+/// or `EnvironmentDetector::detect`: the candidate commands to try in order
+/// (first one that runs successfully wins), and how to pull a version string
+/// out of its output.
+pub struct RuntimeProbe {
+    pub label: &'static str,
+    pub commands: &'static [&'static str],
+    pub extract_version: fn(&str) -> Option<String>,
+}
+
+/// Takes the `idx`-th whitespace-separated word, stripped of quoting/punctuation
+/// commonly found around version numbers (`"17.0.9"`, `1.7.1)`, `24.0.5,`).
+fn word(output: &str, idx: usize) -> Option<String> {
+    output
+        .split_whitespace()
+        .nth(idx)
+        .map(|s| s.trim_matches(|c: char| matches!(c, '"' | ',' | '(' | ')')).to_string())
+}
+
+fn first_word(output: &str) -> Option<String> {
+    word(output, 0)
+}
+
+fn second_word(output: &str) -> Option<String> {
+    word(output, 1)
+}
+
+fn third_word(output: &str) -> Option<String> {
+    word(output, 2)
+}
+
+/// The built-in set of runtimes Prime knows how to fingerprint. Adding a new
+/// one (e.g. zig, bun) only means appending a `RuntimeProbe` here.
+fn runtime_probes() -> Vec<RuntimeProbe> {
+    vec![
+        RuntimeProbe { label: "git", commands: &["git --version"], extract_version: third_word },
+        RuntimeProbe { label: "npm", commands: &["npm --version"], extract_version: first_word },
+        RuntimeProbe { label: "docker", commands: &["docker --version"], extract_version: third_word },
+        RuntimeProbe { label: "rust", commands: &["rustc --version"], extract_version: second_word },
+        RuntimeProbe { label: "cargo", commands: &["cargo --version"], extract_version: second_word },
+        RuntimeProbe { label: "node", commands: &["node --version"], extract_version: first_word },
+        RuntimeProbe { label: "deno", commands: &["deno --version"], extract_version: second_word },
+        RuntimeProbe { label: "go", commands: &["go version"], extract_version: third_word },
+        RuntimeProbe { label: "java", commands: &["java -version", "java --version"], extract_version: third_word },
+        RuntimeProbe { label: "conda", commands: &["conda --version"], extract_version: second_word },
+        RuntimeProbe { label: "poetry", commands: &["poetry --version"], extract_version: third_word },
+    ]
 }
 
 pub struct EnvironmentDetector;
@@ -23,26 +75,35 @@ impl EnvironmentDetector {
     pub fn new() -> Self {
         Self
     }
-    
-    pub fn detect(&self, command_processor: &CommandProcessor) -> EnvironmentInfo {
+
+    pub fn detect(&self, command_processor: &mut CommandProcessor) -> EnvironmentInfo {
+        let mut runtimes = BTreeMap::new();
+        for probe in runtime_probes() {
+            for command in probe.commands {
+                if let Some(output) = command_processor.check_command(command) {
+                    if let Some(version) = (probe.extract_version)(&output) {
+                        runtimes.insert(probe.label.to_string(), version);
+                        break;
+                    }
+                }
+            }
+        }
+
         EnvironmentInfo {
             os: std::env::consts::OS.to_string(),
             python_version: self.get_python_version(command_processor),
             pip_version: self.get_pip_version(command_processor),
             has_sudo: self.check_sudo(command_processor),
             in_venv: self.check_venv(),
-            has_git: command_processor.check_command("git --version").is_some(),
-            has_npm: command_processor.check_command("npm --version").is_some(),
-            has_docker: command_processor.check_command("docker --version").is_some(),
-            has_rust: command_processor.check_command("rustc --version").is_some(),
             shell: self.get_shell(),
+            runtimes,
         }
     }
-    
-    fn get_python_version(&self, command_processor: &CommandProcessor) -> Option<String> {
+
+    fn get_python_version(&self, command_processor: &mut CommandProcessor) -> Option<String> {
         // Try different python commands
         let commands = ["python --version", "python3 --version", "py --version"];
-        
+
         for cmd in &commands {
             if let Some(output) = command_processor.check_command(cmd) {
                 // Parse version from output like "Python 3.9.0"
@@ -51,14 +112,14 @@ impl EnvironmentDetector {
                 }
             }
         }
-        
+
         None
     }
-    
-    fn get_pip_version(&self, command_processor: &CommandProcessor) -> Option<String> {
+
+    fn get_pip_version(&self, command_processor: &mut CommandProcessor) -> Option<String> {
         // Try different pip commands
         let commands = ["pip --version", "pip3 --version", "python -m pip --version"];
-        
+
         for cmd in &commands {
             if let Some(output) = command_processor.check_command(cmd) {
                 // Parse version from output like "pip 20.2.3 from ..."
@@ -67,11 +128,11 @@ impl EnvironmentDetector {
                 }
             }
         }
-        
+
         None
     }
-    
-    fn check_sudo(&self, command_processor: &CommandProcessor) -> bool {
+
+    fn check_sudo(&self, command_processor: &mut CommandProcessor) -> bool {
         #[cfg(target_os = "windows")]
         {
             // Windows doesn't have sudo, check if running as admin
@@ -82,14 +143,14 @@ impl EnvironmentDetector {
             command_processor.check_command("sudo -n true 2>/dev/null").is_some()
         }
     }
-    
+
     fn check_venv(&self) -> bool {
         // Check common virtual environment variables
-        std::env::var("VIRTUAL_ENV").is_ok() || 
+        std::env::var("VIRTUAL_ENV").is_ok() ||
         std::env::var("CONDA_DEFAULT_ENV").is_ok() ||
         std::env::var("PIPENV_ACTIVE").is_ok()
     }
-    
+
     fn get_shell(&self) -> Option<String> {
         #[cfg(target_os = "windows")]
         {
@@ -110,33 +171,35 @@ impl EnvironmentInfo {
         let mut parts = vec![
             format!("OS: {}", self.os),
         ];
-        
+
         if let Some(py) = &self.python_version {
             parts.push(format!("Python: {}", py));
         }
-        
+
         if let Some(pip) = &self.pip_version {
             parts.push(format!("Pip: {}", pip));
         }
-        
+
         if self.in_venv {
             parts.push("Virtual Env: Active".to_string());
         }
-        
-        let tools: Vec<&str> = vec![
-            if self.has_git { Some("git") } else { None },
-            if self.has_npm { Some("npm") } else { None },
-            if self.has_docker { Some("docker") } else { None },
-            if self.has_rust { Some("rust") } else { None },
-        ]
-        .into_iter()
-        .flatten()
-        .collect();
-        
-        if !tools.is_empty() {
+
+        if !self.runtimes.is_empty() {
+            let tools: Vec<String> = self.runtimes
+                .iter()
+                .map(|(label, version)| format!("{} {}", label, version))
+                .collect();
             parts.push(format!("Tools: {}", tools.join(", ")));
         }
-        
+
         parts.join(" | ")
     }
-}
\ No newline at end of file
+
+    /// Serializes to a stable JSON fingerprint (`runtimes` is a `BTreeMap`, so key
+    /// order never shuffles between runs). Callers can hash or diff this string to
+    /// decide whether the system prompt needs to be regenerated, and `!env` can
+    /// print it directly as machine-readable state.
+    pub fn fingerprint(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
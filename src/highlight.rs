@@ -0,0 +1,59 @@
+//! highlight.rs — syntect-backed syntax highlighting for code blocks and file
+//! previews, used by ui.rs's `panel`/`preview` helpers.
+
+use std::io::IsTerminal;
+
+use once_cell::sync::Lazy;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Style, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+/// Loaded once at first use, mirroring `styling::STYLER`.
+static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+
+/// Looks up a syntax by fence-tag language (e.g. "rust", "py") or, failing that,
+/// by file extension (e.g. "rs", "py").
+fn find_syntax(lang_hint: &str) -> Option<&'static SyntaxReference> {
+    SYNTAX_SET
+        .find_syntax_by_token(lang_hint)
+        .or_else(|| SYNTAX_SET.find_syntax_by_extension(lang_hint))
+}
+
+/// Highlights `code` for `lang_hint` (a fence tag or file extension) and returns
+/// ANSI-escaped text ready to print. Falls back to `code` unchanged when the
+/// language can't be resolved or stdout isn't a TTY (so piped/redirected output
+/// stays plain).
+pub fn highlight(code: &str, lang_hint: Option<&str>) -> String {
+    if !std::io::stdout().is_terminal() {
+        return code.to_string();
+    }
+    let Some(lang) = lang_hint else { return code.to_string() };
+    let Some(syntax) = find_syntax(lang) else { return code.to_string() };
+
+    let theme = &THEME_SET.themes["base16-ocean.dark"];
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut out = String::new();
+    for line in LinesWithEndings::from(code) {
+        let ranges: Vec<(Style, &str)> = match highlighter.highlight_line(line, &SYNTAX_SET) {
+            Ok(ranges) => ranges,
+            Err(_) => return code.to_string(),
+        };
+        out.push_str(&as_24_bit_terminal_escaped(&ranges, false));
+    }
+    out.push_str("\x1b[0m");
+    out
+}
+
+/// Extracts the language tag from a fenced code block's opening line, e.g.
+/// "```rust" -> `Some("rust")`. Returns `None` for a bare "```".
+pub fn fence_lang(opening_line: &str) -> Option<&str> {
+    let tag = opening_line.trim().trim_start_matches("```").trim();
+    if tag.is_empty() { None } else { Some(tag) }
+}
+
+/// Infers a language hint from a file path's extension, e.g. "main.rs" -> `Some("rs")`.
+pub fn lang_from_path(path: &str) -> Option<&str> {
+    std::path::Path::new(path).extension().and_then(|e| e.to_str())
+}
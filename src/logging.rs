@@ -2,8 +2,9 @@ use chrono;
 use std::path::PathBuf;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::sync::Mutex;
 use anyhow::Result;
-use serde_json::Value;
+use serde_json::{json, Value};
 use once_cell::sync::Lazy;
 use crate::styling::STYLER;
 
@@ -11,18 +12,62 @@ pub static LOG: Lazy<Logger> = Lazy::new(|| Logger::new());
 
 pub struct Logger {
     debug_logger: DebugLogger,
+    json_sink: Mutex<Option<Box<dyn Write + Send>>>,
 }
 
 impl Logger {
     fn new() -> Self {
+        let json_sink = if Self::json_mode_requested() {
+            Some(Box::new(std::io::stdout()) as Box<dyn Write + Send>)
+        } else {
+            None
+        };
+
         Self {
             debug_logger: DebugLogger::new(true, None, LogLevel::Info),
+            json_sink: Mutex::new(json_sink),
+        }
+    }
+
+    // Whether `--json`/PRIME_LOG_JSON mode is active by default
+    fn json_mode_requested() -> bool {
+        std::env::var("PRIME_LOG_JSON")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+            || std::env::args().any(|a| a == "--json")
+    }
+
+    // Install a custom NDJSON writer (e.g. a file), replacing the default stdout sink.
+    pub fn set_json_sink(&self, writer: Option<Box<dyn Write + Send>>) {
+        *self.json_sink.lock().unwrap() = writer;
+    }
+
+    // Emit one NDJSON object for `event`, merging in event-specific `fields`, if a sink is active.
+    fn emit_json(&self, event: &str, level: &str, fields: Value) {
+        let mut guard = self.json_sink.lock().unwrap();
+        let Some(sink) = guard.as_mut() else { return };
+
+        let mut record = json!({
+            "timestamp": chrono::Local::now().to_rfc3339(),
+            "level": level,
+            "event": event,
+        });
+        if let (Value::Object(record), Value::Object(fields)) = (&mut record, fields) {
+            record.extend(fields);
+        }
+
+        if let Ok(line) = serde_json::to_string(&record) {
+            let _ = writeln!(sink, "{}", line);
         }
     }
 
     // Command execution logging
     pub fn executing(&self, command: impl std::fmt::Display, pwd: impl std::fmt::Display) {
-        println!("{}", STYLER.executing_command_style(pwd, command));
+        println!("{}", STYLER.executing_command_style(&pwd, &command));
+        self.emit_json("executing", "info", json!({
+            "command": command.to_string(),
+            "pwd": pwd.to_string(),
+        }));
     }
 
     // Success messages
@@ -30,8 +75,9 @@ impl Logger {
         println!(
             "{} {}",
             STYLER.success_style("[OK]"),
-            STYLER.success_style(msg)
+            STYLER.success_style(&msg)
         );
+        self.emit_json("success", "info", json!({ "message": msg.to_string() }));
     }
 
     // Error messages
@@ -39,8 +85,9 @@ impl Logger {
         eprintln!(
             "{} {}",
             STYLER.error_style("[ERROR]"),
-            STYLER.error_style(msg)
+            STYLER.error_style(&msg)
         );
+        self.emit_json("error", "error", json!({ "message": msg.to_string() }));
     }
 
     // Warning messages
@@ -48,8 +95,9 @@ impl Logger {
         println!(
             "{} {}",
             STYLER.warning_style("[WARN]"),
-            STYLER.warning_style(msg)
+            STYLER.warning_style(&msg)
         );
+        self.emit_json("warning", "warn", json!({ "message": msg.to_string() }));
     }
 
     // Info messages
@@ -57,8 +105,9 @@ impl Logger {
         println!(
             "{} {}",
             STYLER.info_style("[INFO]"),
-            STYLER.info_style(msg)
+            STYLER.info_style(&msg)
         );
+        self.emit_json("info", "info", json!({ "message": msg.to_string() }));
     }
 
     // Command output preview
@@ -67,10 +116,10 @@ impl Logger {
             let preview_lines = 5;
             let lines: Vec<&str> = output.lines().collect();
             let preview = lines.iter().take(preview_lines).cloned().collect::<Vec<&str>>().join("\n");
-            
+
             println!("{}", STYLER.dim_gray_style("Output preview:"));
             println!("{}", STYLER.dim_gray_style(&preview));
-            
+
             if lines.len() > preview_lines {
                 println!(
                     "{}",
@@ -81,6 +130,7 @@ impl Logger {
                 );
             }
         }
+        self.emit_json("command_output", "info", json!({ "output": output }));
     }
 
     // Header messages
@@ -97,6 +147,7 @@ impl Logger {
                 exit_code
             ))
         );
+        self.emit_json("command_status", "info", json!({ "exit_code": exit_code }));
     }
 }
 
@@ -1,6 +1,7 @@
 //! Entry point for Prime CLI
 //! v0.2.5: Enhanced with streaming responses and rich display
 
+mod alias;
 mod commands;
 mod config;
 mod console;
@@ -9,6 +10,7 @@ mod session;
 mod parser;
 mod streaming;
 mod display;
+mod watch;
 
 use std::env;
 use std::process;
@@ -49,12 +51,33 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Maps a `[provider.profiles.<name>].backend` string to the `LLMBackend` the
+/// `llm` crate understands. New backends only need an arm here; everything
+/// else about wiring a profile into `init_session` is already generic.
+fn resolve_backend(name: &str) -> Result<LLMBackend> {
+    match name {
+        "google" => Ok(LLMBackend::Google),
+        "ollama" => Ok(LLMBackend::Ollama),
+        "openai" => Ok(LLMBackend::OpenAI),
+        "anthropic" => Ok(LLMBackend::Anthropic),
+        other => Err(anyhow::anyhow!("Unsupported provider backend: {}", other)),
+    }
+}
+
 async fn init_session(config: Config) -> Result<PrimeSession> {
-    let provider = env::var("LLM_PROVIDER").unwrap_or(config.provider);
-    let model_from_env = env::var("LLM_MODEL").ok();
-    
-    let model = model_from_env.or(config.model).unwrap_or_else(|| {
-        match provider.as_str() {
+    crate::styling::init_theme(config.theme.clone());
+
+    let active_profile = env::var("LLM_PROVIDER").unwrap_or(config.provider.active);
+    let profile = config.provider.profiles.get(&active_profile).cloned().ok_or_else(|| {
+        anyhow::anyhow!(
+            "No [provider.profiles.{}] entry configured for active provider '{}'",
+            active_profile,
+            active_profile
+        )
+    })?;
+
+    let model = env::var("LLM_MODEL").ok().or(profile.model.clone()).unwrap_or_else(|| {
+        match profile.backend.as_str() {
             "google" => "gemini-2.5-flash-lite".to_string(),
             "ollama" => "gemma2".to_string(),
             _ => "gemma2".to_string(),
@@ -64,12 +87,12 @@ async fn init_session(config: Config) -> Result<PrimeSession> {
     let temperature = env::var("LLM_TEMPERATURE")
         .ok()
         .and_then(|s| s.parse::<f32>().ok())
-        .unwrap_or(config.temperature);
-        
+        .unwrap_or(config.generation.temperature);
+
     let max_tokens = env::var("LLM_MAX_TOKENS")
         .ok()
         .and_then(|s| s.parse::<u32>().ok())
-        .unwrap_or(config.max_tokens);
+        .unwrap_or(config.generation.max_tokens);
 
     let prime_config_base_dir = dirs::home_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -77,40 +100,30 @@ async fn init_session(config: Config) -> Result<PrimeSession> {
 
     let workspace_dir = env::current_dir().context("Failed to get current working directory")?;
 
-    let (llm, provider_name) = match provider.as_str() {
-        "google" => {
-            let api_key = env::var("GEMINI_API_KEY").unwrap_or(config.gemini_api_key);
-            if api_key.is_empty() {
-                return Err(anyhow::anyhow!("GEMINI_API_KEY not set in environment or config.toml. Please get a key from Google AI Studio."));
-            }
-            let llm = LLMBuilder::new()
-                .backend(LLMBackend::Google)
-                .api_key(api_key)
-                .model(model.clone())
-                .max_tokens(max_tokens)
-                .temperature(temperature)
-                .build()
-                .context("Failed to build LLM provider (Google)")?;
-            (llm, "Google AI Platform")
-        },
-        "ollama" => {
-            let api_key = env::var("OLLAMA_API_KEY").unwrap_or(config.ollama_api_key);
-            let llm = LLMBuilder::new()
-                .backend(LLMBackend::Ollama)
-                .api_key(api_key)
-                .model(model.clone())
-                .max_tokens(max_tokens)
-                .temperature(temperature)
-                .build()
-                .context("Failed to build LLM provider (Ollama)")?;
-            (llm, "Ollama")
-        },
-        _ => {
-            return Err(anyhow::anyhow!("Unsupported LLM provider: {}", provider));
-        }
-    };
+    let env_key_var = format!("{}_API_KEY", active_profile.to_uppercase());
+    let api_key = env::var(&env_key_var).unwrap_or(profile.api_key.clone());
+    if api_key.is_empty() && profile.backend == "google" {
+        return Err(anyhow::anyhow!(
+            "No API key configured for provider profile '{}'. Set {} or provider.profiles.{}.api_key in config.toml.",
+            active_profile, env_key_var, active_profile
+        ));
+    }
+
+    let backend = resolve_backend(&profile.backend)?;
+    let mut builder = LLMBuilder::new()
+        .backend(backend)
+        .api_key(api_key)
+        .model(model.clone())
+        .max_tokens(max_tokens)
+        .temperature(temperature);
+    if let Some(base_url) = &profile.base_url {
+        builder = builder.base_url(base_url.clone());
+    }
+    let llm = builder
+        .build()
+        .with_context(|| format!("Failed to build LLM provider (profile '{}')", active_profile))?;
 
-    console::display_init_info(&model, provider_name, &prime_config_base_dir, &workspace_dir);
+    console::display_init_info(&model, &active_profile, &prime_config_base_dir, &workspace_dir);
 
     let session = PrimeSession::new(prime_config_base_dir, llm)?;
 
@@ -1,14 +1,95 @@
 use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 use chrono::Utc;
- 
+
+/// Matches the `## Entry (<timestamp>)` header `write_memory` prepends to
+/// every appended entry, capturing the timestamp so entries can be split
+/// back out of a memory file and scored individually.
+static ENTRY_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^## Entry \(([^)]*)\)\s*$").unwrap());
+
+/// BM25 term-frequency saturation parameter. See `MemoryManager::retrieve`.
+const BM25_K1: f64 = 1.5;
+/// BM25 document-length normalization parameter. See `MemoryManager::retrieve`.
+const BM25_B: f64 = 0.75;
+
+/// One memory entry scored and returned by `MemoryManager::retrieve`.
+#[derive(Debug, Clone)]
+pub struct MemoryHit {
+    pub memory_type: String,
+    pub timestamp: String,
+    pub content: String,
+    pub score: f64,
+}
+
+/// Lowercases `text` and splits on runs of non-alphanumeric characters, so
+/// punctuation never becomes part of a token.
+fn tokenize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Splits a memory file's content on its `## Entry (...)` headers, returning
+/// each entry's timestamp and body. Text before the first header (e.g. the
+/// file's leading `# Prime ... Memory` blurb) is discarded.
+fn split_entries(content: &str) -> Vec<(String, String)> {
+    let headers: Vec<(usize, usize, String)> = ENTRY_HEADER
+        .captures_iter(content)
+        .map(|c| {
+            let m = c.get(0).unwrap();
+            (m.start(), m.end(), c.get(1).unwrap().as_str().to_string())
+        })
+        .collect();
+
+    headers
+        .iter()
+        .enumerate()
+        .map(|(i, (_, end, timestamp))| {
+            let body_start = *end;
+            let body_end = headers.get(i + 1).map(|(start, _, _)| *start).unwrap_or(content.len());
+            (timestamp.clone(), content[body_start..body_end].trim().to_string())
+        })
+        .filter(|(_, body)| !body.is_empty())
+        .collect()
+}
+
+/// Default number of entries `short_term.md` may hold before `write_memory`
+/// triggers an automatic rollup.
+const DEFAULT_SHORT_TERM_MAX_ENTRIES: usize = 50;
+
+/// Outcome of a `MemoryManager::prune` call: how many entries were archived
+/// out of short-term memory, and how many duplicate long-term entries were
+/// merged away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PruneReport {
+    pub archived: usize,
+    pub merged: usize,
+}
+
+/// Condenses an entry's body down to its first line, truncated to 60
+/// characters, for inclusion in a rollup summary entry.
+fn summarize_entry(body: &str) -> String {
+    let first_line = body.lines().next().unwrap_or("").trim();
+    if first_line.chars().count() > 60 {
+        format!("{}...", first_line.chars().take(60).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
 
 /// Manages long-term and short-term memory for the assistant
 #[derive(Debug, Clone)]
 pub struct MemoryManager {
     memory_dir: PathBuf,
+    short_term_max_entries: usize,
 }
 
 impl MemoryManager {
@@ -26,7 +107,14 @@ impl MemoryManager {
                     .with_context(|| format!("Failed to create initial memory file at {}", file_path.display()))?;
             }
         }
-        Ok(Self { memory_dir })
+        Ok(Self { memory_dir, short_term_max_entries: DEFAULT_SHORT_TERM_MAX_ENTRIES })
+    }
+
+    /// Overrides the short-term entry cap (default `DEFAULT_SHORT_TERM_MAX_ENTRIES`)
+    /// that triggers automatic rollup from `write_memory`.
+    pub fn with_short_term_cap(mut self, max_entries: usize) -> Self {
+        self.short_term_max_entries = max_entries;
+        self
     }
 
     /// Reads memory content from the specified file (or both if none specified)
@@ -58,6 +146,95 @@ impl MemoryManager {
         Ok(memory_content)
     }
     
+    /// Ranks every stored entry (both long-term and short-term) against
+    /// `query` using BM25 and returns the top `top_k`, stopping early once
+    /// their combined token length would exceed `token_budget`. This lets
+    /// callers pull only the entries relevant to the current turn instead of
+    /// dumping the whole memory file into the prompt.
+    ///
+    /// Each entry is tokenized (lowercased, split on non-alphanumeric runs);
+    /// a query term `t`'s contribution to an entry's score is
+    /// `IDF(t) * tf*(k1+1) / (tf + k1*(1 - b + b*dl/avgdl))` where
+    /// `IDF(t) = ln((N - df + 0.5)/(df + 0.5) + 1)`, `dl` is the entry's
+    /// token count, and `avgdl` is the mean token count across all entries.
+    /// Ties are broken by recency (entry timestamp, newest first).
+    pub fn retrieve(&self, query: &str, top_k: usize, token_budget: usize) -> Result<Vec<MemoryHit>> {
+        let mut documents: Vec<(String, String, String, Vec<String>)> = Vec::new();
+        for (memory_type, file_name) in [("long_term", "long_term.md"), ("short_term", "short_term.md")] {
+            let content = self.read_file(file_name).unwrap_or_default();
+            for (timestamp, body) in split_entries(&content) {
+                let tokens = tokenize(&body);
+                documents.push((memory_type.to_string(), timestamp, body, tokens));
+            }
+        }
+
+        let n = documents.len();
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+        let avgdl = documents.iter().map(|(_, _, _, tokens)| tokens.len()).sum::<usize>() as f64 / n as f64;
+
+        // Owned keys (instead of `&str` borrowed from `documents`) so this map
+        // can outlive the `for` loop's borrow without fighting the borrow
+        // checker over `documents.into_iter()` below.
+        let mut df: HashMap<String, usize> = HashMap::new();
+        for (_, _, _, tokens) in &documents {
+            let unique_terms: HashSet<&str> = tokens.iter().map(|t| t.as_str()).collect();
+            for term in unique_terms {
+                *df.entry(term.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        let query_terms = tokenize(query);
+        let mut scored: Vec<MemoryHit> = documents
+            .into_iter()
+            .map(|(memory_type, timestamp, content, tokens)| {
+                let dl = tokens.len() as f64;
+                let mut tf: HashMap<&str, usize> = HashMap::new();
+                for t in &tokens {
+                    *tf.entry(t.as_str()).or_insert(0) += 1;
+                }
+                let score: f64 = query_terms
+                    .iter()
+                    .map(|term| {
+                        let term_df = *df.get(term.as_str()).unwrap_or(&0);
+                        if term_df == 0 {
+                            return 0.0;
+                        }
+                        let idf = ((n as f64 - term_df as f64 + 0.5) / (term_df as f64 + 0.5) + 1.0).ln();
+                        let term_tf = *tf.get(term.as_str()).unwrap_or(&0) as f64;
+                        idf * term_tf * (BM25_K1 + 1.0)
+                            / (term_tf + BM25_K1 * (1.0 - BM25_B + BM25_B * dl / avgdl))
+                    })
+                    .sum();
+                MemoryHit { memory_type, timestamp, content, score }
+            })
+            .filter(|hit| hit.score > 0.0)
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| b.timestamp.cmp(&a.timestamp))
+        });
+
+        let mut hits = Vec::new();
+        let mut used_tokens = 0usize;
+        for hit in scored {
+            if hits.len() >= top_k {
+                break;
+            }
+            let hit_tokens = tokenize(&hit.content).len();
+            if used_tokens + hit_tokens > token_budget {
+                break;
+            }
+            used_tokens += hit_tokens;
+            hits.push(hit);
+        }
+        Ok(hits)
+    }
+
     /// Writes content to the specified memory type
     pub fn write_memory(&self, memory_type: &str, content: &str) -> Result<()> {
         let file_name = match memory_type {
@@ -78,9 +255,109 @@ impl MemoryManager {
                 file.write_all(entry.as_bytes())
                     .map_err(|e| anyhow::anyhow!("Failed to write to memory file: {}", e))
             })
-            .with_context(|| format!("Failed to write to memory file: {}", file_path.display()))
+            .with_context(|| format!("Failed to write to memory file: {}", file_path.display()))?;
+
+        if memory_type == "short_term" {
+            self.prune("short_term")?;
+        }
+        Ok(())
     }
-    
+
+    /// Prunes the specified memory type: for `short_term`, archives the
+    /// oldest entries once the entry cap is exceeded, replacing them with a
+    /// single condensed summary entry; for `long_term`, dedupes entries whose
+    /// normalized content is identical. A no-op (zeroed report) when nothing
+    /// needs pruning.
+    pub fn prune(&self, memory_type: &str) -> Result<PruneReport> {
+        match memory_type {
+            "short_term" => self.rollup_short_term(),
+            "long_term" => self.dedupe_long_term(),
+            _ => Err(anyhow!("Invalid memory type '{}' specified", memory_type)),
+        }
+    }
+
+    /// Archives the oldest `short_term.md` entries past the rollup threshold
+    /// (half the entry cap) into a dated archive file, replacing them with a
+    /// single summary entry prepended to the remaining ones.
+    fn rollup_short_term(&self) -> Result<PruneReport> {
+        let file_name = "short_term.md";
+        let content = self.read_file(file_name)?;
+        let entries = split_entries(&content);
+        if entries.len() <= self.short_term_max_entries {
+            return Ok(PruneReport::default());
+        }
+
+        let rollup_keep = self.short_term_max_entries / 2;
+        let to_archive = entries.len() - rollup_keep;
+        let (archived, kept) = entries.split_at(to_archive);
+
+        let archive_path = self
+            .memory_dir
+            .join(format!("short_term_archive_{}.md", Utc::now().format("%Y%m%d_%H%M%S")));
+        let archive_body: String = archived
+            .iter()
+            .map(|(timestamp, body)| format!("\n## Entry ({})\n{}\n", timestamp, body))
+            .collect();
+        fs::write(&archive_path, format!("# Prime Short-term Memory Archive\n{}", archive_body))
+            .with_context(|| format!("Failed to write memory archive: {}", archive_path.display()))?;
+
+        let summary = format!(
+            "Archived {} short-term entries ({} to {}): {}",
+            archived.len(),
+            archived.first().map(|(ts, _)| ts.as_str()).unwrap_or(""),
+            archived.last().map(|(ts, _)| ts.as_str()).unwrap_or(""),
+            archived.iter().map(|(_, body)| summarize_entry(body)).collect::<Vec<_>>().join("; ")
+        );
+
+        let mut rebuilt = "# Prime Short-term Memory\n\n(This file is for notes. The AI will read this.)".to_string();
+        rebuilt.push_str(&format!("\n## Entry ({})\n{}\n", Utc::now(), summary));
+        for (timestamp, body) in kept {
+            rebuilt.push_str(&format!("\n## Entry ({})\n{}\n", timestamp, body));
+        }
+
+        let file_path = self.memory_dir.join(file_name);
+        fs::write(&file_path, rebuilt)
+            .with_context(|| format!("Failed to rewrite memory file: {}", file_path.display()))?;
+
+        Ok(PruneReport { archived: archived.len(), merged: 0 })
+    }
+
+    /// Dedupes `long_term.md` entries whose normalized (lowercased,
+    /// whitespace-collapsed) content is identical, keeping the first
+    /// occurrence of each.
+    fn dedupe_long_term(&self) -> Result<PruneReport> {
+        let file_name = "long_term.md";
+        let content = self.read_file(file_name)?;
+        let entries = split_entries(&content);
+
+        let mut seen = HashSet::new();
+        let mut deduped = Vec::new();
+        let mut merged = 0usize;
+        for (timestamp, body) in entries {
+            let normalized = body.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            if seen.insert(normalized) {
+                deduped.push((timestamp, body));
+            } else {
+                merged += 1;
+            }
+        }
+
+        if merged == 0 {
+            return Ok(PruneReport::default());
+        }
+
+        let mut rebuilt = "# Prime Long-term Memory\n\n(This file is for notes. The AI will read this.)".to_string();
+        for (timestamp, body) in &deduped {
+            rebuilt.push_str(&format!("\n## Entry ({})\n{}\n", timestamp, body));
+        }
+
+        let file_path = self.memory_dir.join(file_name);
+        fs::write(&file_path, rebuilt)
+            .with_context(|| format!("Failed to rewrite memory file: {}", file_path.display()))?;
+
+        Ok(PruneReport { archived: 0, merged })
+    }
+
     /// Clears the specified memory type
     pub fn clear_memory(&self, memory_type: &str) -> Result<()> {
         let file_name = match memory_type {
@@ -103,4 +380,127 @@ impl MemoryManager {
         fs::read_to_string(&file_path)
             .with_context(|| format!("Failed to read memory file: {}", file_path.display()))
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn manager() -> (tempfile::TempDir, MemoryManager) {
+        let dir = tempdir().unwrap();
+        let manager = MemoryManager::new(dir.path().to_path_buf()).unwrap();
+        (dir, manager)
+    }
+
+    /// Writes `## Entry (<timestamp>)` entries directly to `file_name`,
+    /// bypassing `write_memory` so the test controls each entry's timestamp
+    /// precisely instead of racing `Utc::now()`.
+    fn write_entries(manager: &MemoryManager, file_name: &str, entries: &[(&str, &str)]) {
+        let mut body = "# Prime Memory\n\n(This file is for notes. The AI will read this.)".to_string();
+        for (timestamp, content) in entries {
+            body.push_str(&format!("\n## Entry ({})\n{}\n", timestamp, content));
+        }
+        fs::write(manager.memory_dir.join(file_name), body).unwrap();
+    }
+
+    #[test]
+    fn test_retrieve_ranks_by_score_then_breaks_ties_by_recency() {
+        let (_dir, manager) = manager();
+        write_entries(
+            &manager,
+            "long_term.md",
+            &[
+                ("2024-01-01T00:00:00Z", "rust rust rust is a systems language"),
+                ("2024-01-02T00:00:00Z", "rust is fine"),
+                // Identical content to the previous entry: same token
+                // frequencies and length mean an identical BM25 score, so
+                // the only thing left to break the tie is recency.
+                ("2024-01-03T00:00:00Z", "rust is fine"),
+            ],
+        );
+
+        let hits = manager.retrieve("rust", 10, 10_000).unwrap();
+        assert_eq!(hits.len(), 3);
+        // The entry repeating "rust" three times scores highest.
+        assert_eq!(hits[0].timestamp, "2024-01-01T00:00:00Z");
+        // The other two entries tie on score; the newer timestamp sorts first.
+        assert_eq!(hits[1].timestamp, "2024-01-03T00:00:00Z");
+        assert_eq!(hits[2].timestamp, "2024-01-02T00:00:00Z");
+    }
+
+    #[test]
+    fn test_retrieve_stops_at_token_budget() {
+        let (_dir, manager) = manager();
+        write_entries(
+            &manager,
+            "long_term.md",
+            &[
+                ("2024-01-01T00:00:00Z", "alpha alpha alpha alpha alpha alpha alpha alpha"),
+                ("2024-01-02T00:00:00Z", "alpha alpha alpha alpha"),
+                ("2024-01-03T00:00:00Z", "alpha"),
+            ],
+        );
+
+        // top_k is high enough to admit all three, but the budget only has
+        // room for the first (highest-scoring, longest) entry's tokens.
+        let hits = manager.retrieve("alpha", 10, 8).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].timestamp, "2024-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_retrieve_returns_empty_when_nothing_matches() {
+        let (_dir, manager) = manager();
+        write_entries(&manager, "long_term.md", &[("2024-01-01T00:00:00Z", "completely unrelated content")]);
+        let hits = manager.retrieve("nonexistent", 10, 10_000).unwrap();
+        assert!(hits.is_empty());
+    }
+
+    #[test]
+    fn test_rollup_short_term_boundary() {
+        let dir = tempdir().unwrap();
+        let manager = MemoryManager::new(dir.path().to_path_buf()).unwrap().with_short_term_cap(4);
+        let entries: Vec<(String, String)> = (0..4).map(|i| (format!("2024-01-0{}T00:00:00Z", i + 1), format!("entry {}", i))).collect();
+        let entry_refs: Vec<(&str, &str)> = entries.iter().map(|(t, b)| (t.as_str(), b.as_str())).collect();
+        write_entries(&manager, "short_term.md", &entry_refs);
+
+        // At exactly the cap, no rollup should happen yet.
+        let report = manager.prune("short_term").unwrap();
+        assert_eq!(report.archived, 0);
+
+        // One entry past the cap triggers a rollup that archives down to half the cap.
+        let mut entries_over = entry_refs.clone();
+        let fifth = ("2024-01-05T00:00:00Z".to_string(), "entry 4".to_string());
+        entries_over.push((fifth.0.as_str(), fifth.1.as_str()));
+        write_entries(&manager, "short_term.md", &entries_over);
+
+        let report = manager.prune("short_term").unwrap();
+        assert_eq!(report.archived, 3); // 5 entries - (cap=4)/2 kept = 3 archived
+        let remaining = split_entries(&manager.read_file("short_term.md").unwrap());
+        // 1 summary entry + 2 kept entries.
+        assert_eq!(remaining.len(), 3);
+    }
+
+    #[test]
+    fn test_dedupe_long_term_merges_identical_content() {
+        let (_dir, manager) = manager();
+        write_entries(
+            &manager,
+            "long_term.md",
+            &[
+                ("2024-01-01T00:00:00Z", "Remember to water the plants"),
+                ("2024-01-02T00:00:00Z", "remember   to water the plants"),
+                ("2024-01-03T00:00:00Z", "a genuinely different note"),
+            ],
+        );
+
+        let report = manager.prune("long_term").unwrap();
+        assert_eq!(report.merged, 1);
+
+        let remaining = split_entries(&manager.read_file("long_term.md").unwrap());
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].0, "2024-01-01T00:00:00Z");
+        assert_eq!(remaining[1].0, "2024-01-03T00:00:00Z");
+    }
 }
\ No newline at end of file
@@ -2,21 +2,361 @@ use anyhow::{anyhow, Context, Result};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum ToolCall {
-    Shell { command: String },
+    Shell { command: String, timeout: Option<u64> },
     ReadFile { path: String, lines: Option<(usize, usize)> },
     WriteFile { path: String, content: String, append: bool },
     ListDir { path: String },
     ChangeDir { path: String },
     WriteMemory { memory_type: String, content: String },
     ClearMemory { memory_type: String },
-    ScriptTool { name: String, args: Vec<String> },
-    CreateTool { name: String, desc: String, args: String, script_content: String },
+    ScriptTool { name: String, args: Vec<String>, timeout: Option<u64> },
+    CreateTool { name: String, desc: String, args: String, arg_spec: ArgSpec, script_content: String },
+    RunScript { lang: String, args: Option<String>, timeout: Option<u64>, script_content: String },
+    Help { filter: Option<String> },
+    Watch { paths: Vec<String>, debounce_ms: Option<u64> },
+    SetAlias { name: String, expansion: String },
+    ClearAlias { name: String },
+    Archive { paths: Vec<String>, dest: String, format: String },
+    Extract { archive: String, dest: String },
+}
+
+/// A positional argument's declared type within an xflags-style `args` grammar.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArgType {
+    Str,
+    Usize,
+    Int,
+    Path,
+}
+
+/// Parsed form of a `create_tool` `args="..."` grammar, e.g. `<path> <count:usize> [--force]`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ArgSpec {
+    /// (name, type, required)
+    pub positionals: Vec<(String, ArgType, bool)>,
+    /// (long flag name, takes a value)
+    pub flags: Vec<(String, bool)>,
+}
+
+impl ArgSpec {
+    /// Renders a usage string like `<path> <count:usize> [--force] [--out <value>]`,
+    /// mirroring the existing `!read <message_number>` style hint.
+    pub fn usage(&self) -> String {
+        let mut parts = Vec::new();
+        for (name, ty, required) in &self.positionals {
+            let ty_str = match ty {
+                ArgType::Usize => ":usize",
+                ArgType::Int => ":int",
+                ArgType::Path => ":path",
+                ArgType::Str => "",
+            };
+            if *required {
+                parts.push(format!("<{}{}>", name, ty_str));
+            } else {
+                parts.push(format!("<{}{}?>", name, ty_str));
+            }
+        }
+        for (flag, takes_value) in &self.flags {
+            if *takes_value {
+                parts.push(format!("[--{} <value>]", flag));
+            } else {
+                parts.push(format!("[--{}]", flag));
+            }
+        }
+        parts.join(" ")
+    }
+
+    /// Checks `args` (as a `ScriptTool` would receive them) against arity, integer
+    /// parsing for typed positionals, and unknown-flag detection.
+    pub fn validate(&self, args: &[String]) -> std::result::Result<(), String> {
+        let mut positional_values = Vec::new();
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            if let Some(flag_name) = arg.strip_prefix("--") {
+                match self.flags.iter().find(|(f, _)| f == flag_name) {
+                    Some((_, takes_value)) => {
+                        if *takes_value {
+                            i += 1;
+                            if i >= args.len() {
+                                return Err(format!("Flag --{} requires a value. Usage: {}", flag_name, self.usage()));
+                            }
+                        }
+                    }
+                    None => return Err(format!("Unknown flag --{}. Usage: {}", flag_name, self.usage())),
+                }
+            } else {
+                positional_values.push(arg.clone());
+            }
+            i += 1;
+        }
+
+        let required_count = self.positionals.iter().filter(|(_, _, required)| *required).count();
+        if positional_values.len() < required_count {
+            return Err(format!(
+                "Expected at least {} positional argument(s), got {}. Usage: {}",
+                required_count,
+                positional_values.len(),
+                self.usage()
+            ));
+        }
+
+        for (idx, (name, ty, _)) in self.positionals.iter().enumerate() {
+            if let Some(value) = positional_values.get(idx) {
+                let valid = match ty {
+                    ArgType::Usize => value.parse::<usize>().is_ok(),
+                    ArgType::Int => value.parse::<i64>().is_ok(),
+                    ArgType::Str | ArgType::Path => true,
+                };
+                if !valid {
+                    return Err(format!("Argument '{}' expects an integer, got '{}'. Usage: {}", name, value, self.usage()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One argument in a `## TOOL:` header's `args="..."` signature, e.g. the
+/// `path:path?` or `count:int=10` or `--recurse:flag` in
+/// `args="pattern:string path:path? --recurse:flag count:int=10"`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ToolArgSpec {
+    pub name: String,
+    pub ty: ArgType,
+    pub optional: bool,
+    pub default: Option<String>,
+    pub is_flag: bool,
+}
+
+impl ToolArgSpec {
+    /// Renders this argument back in the header grammar it was parsed from
+    /// (`name:type`, `name:type?`, `name:type=default`, or `--flag`), for
+    /// precise display in the system prompt and usage errors.
+    pub fn render(&self) -> String {
+        if self.is_flag {
+            return format!("--{}", self.name);
+        }
+        let ty_str = match self.ty {
+            ArgType::Str => String::new(),
+            ArgType::Usize | ArgType::Int => ":int".to_string(),
+            ArgType::Path => ":path".to_string(),
+        };
+        match (&self.default, self.optional) {
+            (Some(default), _) => format!("{}{}={}", self.name, ty_str, default),
+            (None, true) => format!("{}{}?", self.name, ty_str),
+            (None, false) => format!("{}{}", self.name, ty_str),
+        }
+    }
+}
+
+/// Renders a full `ToolArgSpec` signature as a usage string, e.g.
+/// `pattern path:path? --recurse count:int=10`.
+pub fn render_tool_args(specs: &[ToolArgSpec]) -> String {
+    specs.iter().map(ToolArgSpec::render).collect::<Vec<_>>().join(" ")
+}
+
+/// Checks `args` (as a `ScriptTool` would receive them) against `specs`: unknown
+/// flags, missing required positionals, and integer parsing for `int`/`usize`
+/// positionals. Positionals are matched by order among the non-flag args, the
+/// same convention `ArgSpec::validate` uses for `create_tool` scripts.
+pub fn validate_tool_args(specs: &[ToolArgSpec], args: &[String]) -> std::result::Result<(), String> {
+    let usage = render_tool_args(specs);
+    let flag_names: Vec<&str> = specs.iter().filter(|s| s.is_flag).map(|s| s.name.as_str()).collect();
+    let positionals: Vec<&ToolArgSpec> = specs.iter().filter(|s| !s.is_flag).collect();
+
+    let mut positional_values = Vec::new();
+    for arg in args {
+        if let Some(flag_name) = arg.strip_prefix("--") {
+            if !flag_names.contains(&flag_name) {
+                return Err(format!("Unknown flag --{}. Usage: {}", flag_name, usage));
+            }
+        } else {
+            positional_values.push(arg.clone());
+        }
+    }
+
+    let required_count = positionals.iter().filter(|s| !s.optional).count();
+    if positional_values.len() < required_count {
+        return Err(format!(
+            "Expected at least {} positional argument(s), got {}. Usage: {}",
+            required_count,
+            positional_values.len(),
+            usage
+        ));
+    }
+
+    for (idx, spec) in positionals.iter().enumerate() {
+        if let Some(value) = positional_values.get(idx) {
+            let valid = match spec.ty {
+                ArgType::Usize => value.parse::<usize>().is_ok(),
+                ArgType::Int => value.parse::<i64>().is_ok(),
+                ArgType::Str | ArgType::Path => true,
+            };
+            if !valid {
+                return Err(format!("Argument '{}' expects an integer, got '{}'. Usage: {}", spec.name, value, usage));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses a space-separated `args="..."` header signature (distinct from the
+/// `<name> [--flag]` bracket grammar `parse_arg_spec` uses for `create_tool`)
+/// into one `ToolArgSpec` per token: `name`, `name:type`, `name:type?`,
+/// `name:type=default`, or `--name:flag`.
+pub fn parse_tool_arg_spec(spec: &str) -> Result<Vec<ToolArgSpec>> {
+    spec.split_whitespace().map(parse_tool_arg_token).collect()
+}
+
+fn parse_tool_arg_token(token: &str) -> Result<ToolArgSpec> {
+    if let Some(flag_name) = token.strip_prefix("--") {
+        let name = flag_name.split(':').next().unwrap_or(flag_name).to_string();
+        if name.is_empty() {
+            return Err(anyhow!("Invalid flag token '{}': missing a name", token));
+        }
+        return Ok(ToolArgSpec { name, ty: ArgType::Str, optional: true, default: None, is_flag: true });
+    }
+
+    let (name_ty, default) = match token.split_once('=') {
+        Some((n, d)) => (n, Some(d.to_string())),
+        None => (token, None),
+    };
+    let (name, ty_str) = match name_ty.split_once(':') {
+        Some((n, t)) => (n.to_string(), t.to_string()),
+        None => (name_ty.to_string(), String::new()),
+    };
+    if name.is_empty() {
+        return Err(anyhow!("Invalid arg token '{}': missing a name", token));
+    }
+    let (ty_str, explicit_optional) = match ty_str.strip_suffix('?') {
+        Some(stripped) => (stripped.to_string(), true),
+        None => (ty_str, false),
+    };
+    let ty = parse_arg_type(&ty_str)?;
+    let optional = explicit_optional || default.is_some();
+    Ok(ToolArgSpec { name, ty, optional, default, is_flag: false })
+}
+
+/// Splits an `args` grammar into `<...>` and `[...]` tokens, preserving any spaces
+/// inside each bracketed group (e.g. `[--out <file>]` stays one token).
+fn tokenize_arg_spec(spec: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = spec.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut tok = String::new();
+        if c == '[' {
+            let mut depth = 0;
+            while let Some(&c2) = chars.peek() {
+                tok.push(c2);
+                chars.next();
+                if c2 == '[' { depth += 1; }
+                if c2 == ']' { depth -= 1; if depth == 0 { break; } }
+            }
+        } else if c == '<' {
+            while let Some(&c2) = chars.peek() {
+                tok.push(c2);
+                chars.next();
+                if c2 == '>' { break; }
+            }
+        } else {
+            while let Some(&c2) = chars.peek() {
+                if c2.is_whitespace() { break; }
+                tok.push(c2);
+                chars.next();
+            }
+        }
+        tokens.push(tok);
+    }
+    tokens
+}
+
+fn parse_arg_type(ty: &str) -> Result<ArgType> {
+    match ty {
+        "usize" => Ok(ArgType::Usize),
+        "int" => Ok(ArgType::Int),
+        "path" => Ok(ArgType::Path),
+        "string" | "str" | "" => Ok(ArgType::Str),
+        other => Err(anyhow!("Unknown arg type '{}' (expected 'string', 'int', 'path', or 'usize')", other)),
+    }
+}
+
+/// Parses an `args="..."` grammar like `<path> <count:usize> [--force] [--out <file>]`
+/// into a structured `ArgSpec`.
+pub fn parse_arg_spec(spec: &str) -> Result<ArgSpec> {
+    let mut arg_spec = ArgSpec::default();
+    for token in tokenize_arg_spec(spec) {
+        if token.starts_with('<') && token.ends_with('>') {
+            let inner = &token[1..token.len() - 1];
+            let (name_ty, required) = match inner.strip_suffix('?') {
+                Some(stripped) => (stripped, false),
+                None => (inner, true),
+            };
+            let (name, ty) = match name_ty.split_once(':') {
+                Some((n, t)) => (n.to_string(), parse_arg_type(t)?),
+                None => (name_ty.to_string(), ArgType::Str),
+            };
+            arg_spec.positionals.push((name, ty, required));
+        } else if token.starts_with('[') && token.ends_with(']') {
+            let inner = token[1..token.len() - 1].trim();
+            let flag_body = inner
+                .strip_prefix("--")
+                .ok_or_else(|| anyhow!("Invalid flag spec '{}': flags must start with --", token))?;
+            match flag_body.split_once(' ') {
+                Some((flag, _value_placeholder)) => arg_spec.flags.push((flag.trim().to_string(), true)),
+                None => arg_spec.flags.push((flag_body.trim().to_string(), false)),
+            }
+        } else {
+            return Err(anyhow!("Invalid arg spec token '{}': expected <name> or [--flag]", token));
+        }
+    }
+    Ok(arg_spec)
+}
+
+/// One line of a `primeactions` block: the tool call itself, plus an optional
+/// `$name = ` binding that captures its output for later `${name}` substitution.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Step {
+    pub bind: Option<String>,
+    pub tool_call: ToolCall,
 }
 
 #[derive(Debug, Default)]
 pub struct ParsedResponse {
     pub natural_language: String,
-    pub tool_calls: Vec<ToolCall>,
+    pub steps: Vec<Step>,
+}
+
+/// Validates a binding name against `[A-Za-z_][A-Za-z0-9_]*` so it can't collide
+/// with shell `$` usage once substituted back into a command string.
+fn is_valid_bind_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Strips a leading `$name = ` binding prefix from a `primeactions` line, returning
+/// the validated binding name (if any) and the remainder of the line to parse as usual.
+fn parse_bind_prefix(line: &str) -> Result<(Option<String>, &str)> {
+    let trimmed = line.trim_start();
+    if let Some(after_dollar) = trimmed.strip_prefix('$') {
+        if let Some(eq_idx) = after_dollar.find('=') {
+            let name = after_dollar[..eq_idx].trim();
+            if !is_valid_bind_name(name) {
+                return Err(anyhow!("Invalid binding name '{}': must match [A-Za-z_][A-Za-z0-9_]*", name));
+            }
+            let rest = after_dollar[eq_idx + 1..].trim_start();
+            return Ok((Some(name.to_string()), rest));
+        }
+    }
+    Ok((None, line))
 }
 
 fn parse_write_args(args_str: &str) -> (String, bool) {
@@ -31,6 +371,55 @@ fn parse_write_args(args_str: &str) -> (String, bool) {
     (path.trim().to_string(), append)
 }
 
+/// Parses a `shell` args string: the raw command text with an optional
+/// trailing `timeout=N` token stripped off, mirroring how `parse_write_args`
+/// strips a trailing `append=true`. `timeout=0` is the documented opt-out
+/// (no hard timeout), `None` means the caller's default applies.
+fn parse_shell_args(args_str: &str) -> Result<(String, Option<u64>)> {
+    let mut command = args_str.to_string();
+    let mut timeout = None;
+    if let Some(pos) = command.rfind(" timeout=") {
+        let value = command[pos + " timeout=".len()..].trim();
+        if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+            timeout = Some(value.parse::<u64>().context(format!("Invalid timeout value: {}", value))?);
+            command.truncate(pos);
+        }
+    }
+    Ok((command, timeout))
+}
+
+/// Strips a trailing `timeout=N` token off a `ScriptTool`'s whitespace-split
+/// args, the same opt-out convention `parse_shell_args` uses, so custom
+/// tools get the same hang protection without the token being handed to the
+/// script as a positional argument.
+fn take_trailing_timeout_arg(args: &mut Vec<String>) -> Result<Option<u64>> {
+    if let Some(last) = args.last() {
+        if let Some(value) = last.strip_prefix("timeout=") {
+            let timeout = value.parse::<u64>().context(format!("Invalid timeout value: {}", value))?;
+            args.pop();
+            return Ok(Some(timeout));
+        }
+    }
+    Ok(None)
+}
+
+/// Parses an `alias: <name> = <expansion>` args string into its name and
+/// expansion text, e.g. `gs = shell: git status` -> `("gs", "shell: git status")`.
+fn parse_alias_args(args_str: &str) -> Result<(String, String)> {
+    let (name, expansion) = args_str
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Invalid alias args: expected '<name> = <expansion>'"))?;
+    let name = name.trim().to_string();
+    let expansion = expansion.trim().to_string();
+    if name.is_empty() {
+        return Err(anyhow!("Invalid alias args: missing name"));
+    }
+    if expansion.is_empty() {
+        return Err(anyhow!("Invalid alias args: missing expansion"));
+    }
+    Ok((name, expansion))
+}
+
 fn parse_read_args(args_str: &str) -> Result<(String, Option<(usize, usize)>)> {
     if let Some(pos) = args_str.rfind(" lines=") {
         let path = args_str[..pos].trim().to_string();
@@ -51,6 +440,64 @@ fn parse_read_args(args_str: &str) -> Result<(String, Option<(usize, usize)>)> {
     Ok((args_str.trim().to_string(), None))
 }
 
+/// Parses an `archive: <path>... dest=<dest> [format=xz|gz]` args string,
+/// mirroring `parse_watch_args`'s "strip trailing key=value tokens, then
+/// whitespace-split what's left into paths" shape. Defaults `format` to `xz`.
+fn parse_archive_args(args_str: &str) -> Result<(Vec<String>, String, String)> {
+    let mut rest = args_str.to_string();
+    let mut format = "xz".to_string();
+    if let Some(pos) = rest.rfind(" format=") {
+        format = rest[pos + " format=".len()..].trim().to_string();
+        rest.truncate(pos);
+    }
+    if format != "xz" && format != "gz" {
+        return Err(anyhow!("Invalid archive format '{}': expected 'xz' or 'gz'", format));
+    }
+    let pos = rest.rfind(" dest=").ok_or_else(|| anyhow!("Invalid archive args: missing dest=<path>"))?;
+    let dest = rest[pos + " dest=".len()..].trim().to_string();
+    if dest.is_empty() {
+        return Err(anyhow!("Invalid archive args: missing dest=<path>"));
+    }
+    rest.truncate(pos);
+    let paths: Vec<String> = rest.split_whitespace().map(|s| s.to_string()).collect();
+    if paths.is_empty() {
+        return Err(anyhow!("Invalid archive args: at least one path is required"));
+    }
+    Ok((paths, dest, format))
+}
+
+/// Parses an `extract: <archive> dest=<dest>` args string.
+fn parse_extract_args(args_str: &str) -> Result<(String, String)> {
+    let pos = args_str.rfind(" dest=").ok_or_else(|| anyhow!("Invalid extract args: missing dest=<path>"))?;
+    let archive = args_str[..pos].trim().to_string();
+    let dest = args_str[pos + " dest=".len()..].trim().to_string();
+    if archive.is_empty() {
+        return Err(anyhow!("Invalid extract args: missing archive path"));
+    }
+    if dest.is_empty() {
+        return Err(anyhow!("Invalid extract args: missing dest=<path>"));
+    }
+    Ok((archive, dest))
+}
+
+/// Parses a `watch` args string: a space-separated list of paths with an
+/// optional trailing `debounce_ms=N` token, mirroring how `parse_write_args`
+/// strips a trailing `append=true`.
+fn parse_watch_args(args_str: &str) -> Result<(Vec<String>, Option<u64>)> {
+    let mut debounce_ms = None;
+    let mut rest = args_str.to_string();
+    if let Some(pos) = rest.rfind(" debounce_ms=") {
+        let value = rest[pos + " debounce_ms=".len()..].trim();
+        debounce_ms = Some(value.parse::<u64>().context(format!("Invalid debounce_ms value: {}", value))?);
+        rest.truncate(pos);
+    }
+    let paths: Vec<String> = rest.split_whitespace().map(|s| s.to_string()).collect();
+    if paths.is_empty() {
+        return Err(anyhow!("Invalid watch args: at least one path is required"));
+    }
+    Ok((paths, debounce_ms))
+}
+
 fn find_primeactions_block(input: &str) -> (String, Vec<&str>) {
     let lines: Vec<&str> = input.lines().collect();
     let mut natural = String::new();
@@ -129,6 +576,58 @@ fn parse_create_tool_args(args_str: &str) -> Result<(String, String, String)> {
     Ok((name, desc, args_spec))
 }
 
+/// Parses a `run_script` `lang=python [args="..."] [timeout=30]` args string.
+/// `args`' value is quote-delimited (it may itself contain spaces); `lang`
+/// and `timeout` are bare tokens. Unlike `parse_create_tool_args`, which
+/// only accepts quoted values, any value here may be quoted or bare.
+fn parse_run_script_args(args_str: &str) -> Result<(String, Option<String>, Option<u64>)> {
+    let mut lang = None;
+    let mut args = None;
+    let mut timeout = None;
+    let mut chars = args_str.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |&c| c.is_whitespace()) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+        let mut key = String::new();
+        while chars.peek().map_or(false, |&c| c != '=' && !c.is_whitespace()) {
+            key.push(chars.next().unwrap());
+        }
+        if chars.peek() != Some(&'=') {
+            continue;
+        }
+        chars.next();
+        let value = if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut v = String::new();
+            while let Some(c) = chars.next() {
+                if c == '"' {
+                    break;
+                }
+                v.push(c);
+            }
+            v
+        } else {
+            let mut v = String::new();
+            while chars.peek().map_or(false, |&c| !c.is_whitespace()) {
+                v.push(chars.next().unwrap());
+            }
+            v
+        };
+        match key.as_str() {
+            "lang" => lang = Some(value),
+            "args" => args = Some(value),
+            "timeout" => timeout = Some(value.parse::<u64>().context(format!("Invalid timeout value: {}", value))?),
+            _ => {}
+        }
+    }
+    let lang = lang.ok_or_else(|| anyhow!("Invalid run_script args: missing lang"))?;
+    Ok((lang, args, timeout))
+}
+
 pub fn parse_llm_response(input: &str) -> Result<ParsedResponse> {
     let mut resp = ParsedResponse::default();
     let (natural, block_lines) = find_primeactions_block(input);
@@ -139,14 +638,16 @@ pub fn parse_llm_response(input: &str) -> Result<ParsedResponse> {
         if trimmed.is_empty() {
             continue;
         }
+        let (bind, trimmed) = parse_bind_prefix(trimmed)?;
         let (tool_name, args_str) = match trimmed.split_once(':') {
             Some((t, a)) => (t.trim(), a.trim()),
             None => continue,
         };
         let tool_call = match tool_name {
-            "shell" => ToolCall::Shell {
-                command: args_str.into(),
-            },
+            "shell" => {
+                let (command, timeout) = parse_shell_args(args_str)?;
+                ToolCall::Shell { command, timeout }
+            }
             "list_dir" => ToolCall::ListDir {
                 path: args_str.into(),
             },
@@ -177,6 +678,33 @@ pub fn parse_llm_response(input: &str) -> Result<ParsedResponse> {
                     memory_type: args_str.to_string(),
                 }
             }
+            "help" => {
+                let filter = args_str.trim();
+                ToolCall::Help { filter: if filter.is_empty() { None } else { Some(filter.to_string()) } }
+            }
+            "watch" => {
+                let (paths, debounce_ms) = parse_watch_args(args_str)?;
+                ToolCall::Watch { paths, debounce_ms }
+            }
+            "alias" => {
+                let (name, expansion) = parse_alias_args(args_str)?;
+                ToolCall::SetAlias { name, expansion }
+            }
+            "unalias" => {
+                let name = args_str.trim().to_string();
+                if name.is_empty() {
+                    return Err(anyhow!("Invalid unalias args: missing name"));
+                }
+                ToolCall::ClearAlias { name }
+            }
+            "archive" => {
+                let (paths, dest, format) = parse_archive_args(args_str)?;
+                ToolCall::Archive { paths, dest, format }
+            }
+            "extract" => {
+                let (archive, dest) = parse_extract_args(args_str)?;
+                ToolCall::Extract { archive, dest }
+            }
             "write_file" => {
                 let (path, append) = parse_write_args(args_str);
                 let mut content_lines = Vec::new();
@@ -192,6 +720,17 @@ pub fn parse_llm_response(input: &str) -> Result<ParsedResponse> {
                     append,
                 }
             }
+            "run_script" => {
+                let (lang, args, timeout) = parse_run_script_args(args_str)?;
+                let mut content_lines = Vec::new();
+                while let Some(cl) = lines_iter.next() {
+                    if cl.trim() == "EOF_PRIME" {
+                        break;
+                    }
+                    content_lines.push(cl);
+                }
+                ToolCall::RunScript { lang, args, timeout, script_content: content_lines.join("\n") }
+            }
             "create_tool" => {
                 let (name, desc, args_spec) = parse_create_tool_args(args_str)?;
                 let mut content_lines = Vec::new();
@@ -202,17 +741,20 @@ pub fn parse_llm_response(input: &str) -> Result<ParsedResponse> {
                     content_lines.push(cl);
                 }
                 let script_content = content_lines.join("\n");
-                ToolCall::CreateTool { name, desc, args: args_spec, script_content }
+                let arg_spec = parse_arg_spec(&args_spec)?;
+                ToolCall::CreateTool { name, desc, args: args_spec, arg_spec, script_content }
             }
             _ => {
-                let parts: Vec<_> = args_str.split_whitespace().map(|s| s.to_string()).collect();
+                let mut parts: Vec<_> = args_str.split_whitespace().map(|s| s.to_string()).collect();
+                let timeout = take_trailing_timeout_arg(&mut parts)?;
                 ToolCall::ScriptTool {
                     name: tool_name.to_string(),
                     args: parts,
+                    timeout,
                 }
             }
         };
-        resp.tool_calls.push(tool_call);
+        resp.steps.push(Step { bind, tool_call });
     }
     Ok(resp)
 }
\ No newline at end of file
@@ -0,0 +1,294 @@
+//! plugin.rs — stdio-based tool plugin protocol, modeled on how nushell loads
+//! plugins: each plugin is a standalone executable under `~/.prime/plugins`
+//! that speaks line-delimited JSON-RPC over its stdin/stdout. A `config` call
+//! asks the plugin for its tool signature (name/description/args); an
+//! `invoke` call passes arguments and gets back its textual result. This lets
+//! users extend Prime with tools written in any language, without
+//! recompiling. Spawns each plugin with its own piped `std::process::Command`
+//! rather than going through `CommandProcessor`.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// One JSON-RPC request written to a plugin's stdin, terminated by a newline.
+#[derive(Serialize)]
+struct JsonRpc {
+    method: &'static str,
+    params: Value,
+}
+
+/// A plugin's response to a `config` call: its tool signature, in the same
+/// shape as `## TOOL:` headers on built-in script tools.
+#[derive(serde::Deserialize, Debug, Clone)]
+struct PluginConfig {
+    name: String,
+    desc: String,
+    #[serde(default)]
+    args: String,
+}
+
+/// A discovered plugin, ready to be invoked.
+#[derive(Debug, Clone)]
+pub struct PluginTool {
+    pub name: String,
+    pub desc: String,
+    pub args: String,
+    pub path: PathBuf,
+}
+
+/// Discovers and invokes stdio tool plugins from `~/.prime/plugins`.
+pub struct PluginManager {
+    pub tools: Vec<PluginTool>,
+}
+
+impl PluginManager {
+    /// Scans `plugins_dir` for executable files and asks each one for its
+    /// `config` over a freshly spawned process. A plugin that fails to start
+    /// or returns malformed JSON is skipped with a warning rather than
+    /// failing discovery for the rest.
+    pub fn discover(plugins_dir: &Path) -> Self {
+        let mut tools = Vec::new();
+        let Ok(entries) = std::fs::read_dir(plugins_dir) else {
+            return Self { tools };
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if !is_executable(&path) {
+                continue;
+            }
+            match Self::request_config(&path) {
+                Ok(config) => tools.push(PluginTool { name: config.name, desc: config.desc, args: config.args, path }),
+                Err(e) => eprintln!("Warning: Failed to load plugin '{}': {}", path.display(), e),
+            }
+        }
+        Self { tools }
+    }
+
+    fn request_config(path: &Path) -> Result<PluginConfig> {
+        let response = send_request(path, "config", Value::Null)?;
+        serde_json::from_value(response).context("Plugin returned an invalid config response")
+    }
+
+    /// Invokes `tool_name` with `args`, returning the plugin's textual result.
+    pub fn invoke(&self, tool_name: &str, args: &[String]) -> Result<String> {
+        let tool = self
+            .tools
+            .iter()
+            .find(|t| t.name == tool_name)
+            .ok_or_else(|| anyhow!("No plugin registered for tool '{}'", tool_name))?;
+        let params = serde_json::json!({ "args": args });
+        let response = send_request(&tool.path, "invoke", params)?;
+        match response.get("output").and_then(Value::as_str) {
+            Some(output) => Ok(output.to_string()),
+            None => Ok(response.to_string()),
+        }
+    }
+}
+
+/// Spawns `path` with piped stdio, writes one newline-terminated JSON-RPC
+/// request, and reads one line back as the JSON response.
+fn send_request(path: &Path, method: &'static str, params: Value) -> Result<Value> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to spawn plugin: {}", path.display()))?;
+
+    let request = JsonRpc { method, params };
+    let mut request_line = serde_json::to_string(&request).context("Failed to serialize plugin request")?;
+    request_line.push('\n');
+
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open plugin stdin"))?
+        .write_all(request_line.as_bytes())
+        .with_context(|| format!("Failed to write to plugin: {}", path.display()))?;
+
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("Failed to open plugin stdout"))?;
+    let mut response_line = String::new();
+    BufReader::new(stdout)
+        .read_line(&mut response_line)
+        .with_context(|| format!("Failed to read response from plugin: {}", path.display()))?;
+
+    child.wait().ok();
+
+    serde_json::from_str(response_line.trim())
+        .with_context(|| format!("Plugin returned invalid JSON: {}", response_line.trim()))
+}
+
+/// A long-lived plugin executable (`./prime/plugin_*`), spawned once and driven
+/// over a persistent line-delimited JSON-RPC 2.0 connection instead of being
+/// re-spawned for every call, so it can hold state (caches, open connections,
+/// warmed-up interpreters) across invocations. Mirrors `PluginTool`'s
+/// `config`/`invoke` shape, but the handshake method is `describe` and the
+/// call method is `run`, each framed with a `jsonrpc`/`id` envelope.
+pub struct LivePlugin {
+    pub name: String,
+    pub desc: String,
+    pub args: String,
+    pub path: PathBuf,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+impl LivePlugin {
+    /// Spawns `path` with piped stdio and sends a `describe` call to learn its
+    /// tool signature, keeping the child process alive for subsequent `run` calls.
+    fn spawn(path: PathBuf) -> Result<Self> {
+        let mut child = Command::new(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to spawn plugin: {}", path.display()))?;
+
+        let stdin = child.stdin.take().ok_or_else(|| anyhow!("Failed to open plugin stdin"))?;
+        let stdout = BufReader::new(child.stdout.take().ok_or_else(|| anyhow!("Failed to open plugin stdout"))?);
+
+        let mut plugin = Self {
+            name: String::new(),
+            desc: String::new(),
+            args: String::new(),
+            path,
+            child,
+            stdin,
+            stdout,
+            next_id: 0,
+        };
+
+        let response = plugin.call("describe", Value::Null)?;
+        let config: PluginConfig = serde_json::from_value(response)
+            .context("Plugin returned an invalid describe response")?;
+        plugin.name = config.name;
+        plugin.desc = config.desc;
+        plugin.args = config.args;
+        Ok(plugin)
+    }
+
+    /// Writes one `{"jsonrpc":"2.0","id":N,"method":...}` request and reads the
+    /// matching single-line response, unwrapping `result` or surfacing `error`.
+    fn call(&mut self, method: &'static str, params: Value) -> Result<Value> {
+        self.next_id += 1;
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id,
+            "method": method,
+            "params": params,
+        });
+        let mut request_line = serde_json::to_string(&request).context("Failed to serialize plugin request")?;
+        request_line.push('\n');
+        self.stdin
+            .write_all(request_line.as_bytes())
+            .with_context(|| format!("Failed to write to plugin: {}", self.path.display()))?;
+        self.stdin.flush().ok();
+
+        let mut response_line = String::new();
+        self.stdout
+            .read_line(&mut response_line)
+            .with_context(|| format!("Failed to read response from plugin: {}", self.path.display()))?;
+        if response_line.trim().is_empty() {
+            return Err(anyhow!("Plugin '{}' closed its connection", self.path.display()));
+        }
+
+        let response: Value = serde_json::from_str(response_line.trim())
+            .with_context(|| format!("Plugin returned invalid JSON: {}", response_line.trim()))?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("Plugin '{}' returned an error: {}", self.path.display(), error));
+        }
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| anyhow!("Plugin '{}' response missing 'result'", self.path.display()))
+    }
+
+    /// Sends a `run` request with `args` and the invoking `cwd`, mapping the
+    /// plugin's `{stdout, exit_code}` result into the same success/failure
+    /// convention as a shelled-out script.
+    pub fn invoke(&mut self, args: &[String], cwd: &Path) -> Result<String> {
+        let params = serde_json::json!({ "args": args, "cwd": cwd.to_string_lossy() });
+        let result = self.call("run", params)?;
+        let stdout = result.get("stdout").and_then(Value::as_str).unwrap_or_default();
+        let exit_code = result.get("exit_code").and_then(Value::as_i64).unwrap_or(0);
+        if exit_code == 0 {
+            Ok(stdout.to_string())
+        } else {
+            Err(anyhow!("exited with code {}\nOutput:\n{}", exit_code, stdout))
+        }
+    }
+}
+
+impl Drop for LivePlugin {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+/// Discovers and holds open the long-lived `./prime/plugin_*` executables.
+pub struct LivePluginManager {
+    pub plugins: Vec<LivePlugin>,
+}
+
+impl LivePluginManager {
+    /// Scans `prime_dir` for executables named `plugin_*` and spawns each one,
+    /// skipping (with a warning) any that fail to start or describe themselves.
+    pub fn discover(prime_dir: &Path) -> Self {
+        let mut plugins = Vec::new();
+        let Ok(entries) = std::fs::read_dir(prime_dir) else {
+            return Self { plugins };
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let is_plugin = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| n.starts_with("plugin_"))
+                .unwrap_or(false);
+            if !is_plugin || !is_executable(&path) {
+                continue;
+            }
+            match LivePlugin::spawn(path.clone()) {
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => eprintln!("Warning: Failed to load live plugin '{}': {}", path.display(), e),
+            }
+        }
+        Self { plugins }
+    }
+
+    /// Invokes `tool_name` with `args` against its live plugin connection, if one is registered.
+    pub fn invoke(&mut self, tool_name: &str, args: &[String], cwd: &Path) -> Result<String> {
+        let plugin = self
+            .plugins
+            .iter_mut()
+            .find(|p| p.name == tool_name)
+            .ok_or_else(|| anyhow!("No live plugin registered for tool '{}'", tool_name))?;
+        plugin.invoke(args, cwd)
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.is_file()
+        && std::fs::metadata(path)
+            .map(|m| m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("exe"))
+            .unwrap_or(false)
+}
@@ -1,30 +1,217 @@
  
  
 use std::fmt;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::fs;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 use anyhow::{anyhow, Context as AnyhowContext, Result};
 use crossterm::style::Stylize;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use indicatif::{ProgressBar, ProgressStyle};
 use llm::chat::{ChatMessage, ChatMessageBuilder, ChatProvider, ChatRole};
 use textwrap::{wrap, Options};
-use crate::commands::CommandProcessor;
+use crate::alias::AliasMap;
+use crate::commands::{self, CommandProcessor};
 use crate::memory::MemoryManager;
-use crate::parser::{self, ToolCall};
-use glob::glob;
+use crate::parser::{self, Step, ToolCall};
+use crate::plugin::{LivePluginManager, PluginManager};
+use crate::watch;
+use std::collections::HashMap;
+use std::time::Duration;
+use glob::{glob, Pattern};
 
 const SPINNER_TICKS: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
+/// Session logs past this size are stored gzip-compressed (as `<log>.gz`)
+/// instead of plain markdown, since long agent sessions otherwise leave very
+/// large uncompressed files on disk. Once a log crosses the threshold it
+/// stays compressed for the rest of the session, even if later reads/writes
+/// shrink the in-memory content below it.
+const LOG_COMPRESSION_THRESHOLD_BYTES: usize = 2 * 1024 * 1024;
+
 fn wrap_text(text: &str, width: usize) -> String {
     wrap(text, Options::new(width).break_words(false)).join("\n")
 }
 
+/// Replaces every `${name}` occurrence in `text` with its bound value, erroring
+/// (rather than passing the literal text through) when `name` has no prior binding.
+fn substitute(text: &str, bindings: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let name = &after[..end];
+                match bindings.get(name) {
+                    Some(value) => out.push_str(value),
+                    None => return Err(format!("Unresolved pipeline variable '${{{}}}': no prior step bound '{}'", name, name)),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Applies `${name}` substitution over every string field of a `ToolCall` just
+/// before dispatch, so one step's captured output can feed a later one.
+fn substitute_tool_call(tool_call: ToolCall, bindings: &HashMap<String, String>) -> Result<ToolCall, String> {
+    Ok(match tool_call {
+        ToolCall::Shell { command, timeout } => ToolCall::Shell { command: substitute(&command, bindings)?, timeout },
+        ToolCall::ReadFile { path, lines } => ToolCall::ReadFile { path: substitute(&path, bindings)?, lines },
+        ToolCall::WriteFile { path, content, append } => ToolCall::WriteFile {
+            path: substitute(&path, bindings)?,
+            content: substitute(&content, bindings)?,
+            append,
+        },
+        ToolCall::ListDir { path } => ToolCall::ListDir { path: substitute(&path, bindings)? },
+        ToolCall::ChangeDir { path } => ToolCall::ChangeDir { path: substitute(&path, bindings)? },
+        ToolCall::WriteMemory { memory_type, content } => ToolCall::WriteMemory {
+            memory_type,
+            content: substitute(&content, bindings)?,
+        },
+        ToolCall::ClearMemory { memory_type } => ToolCall::ClearMemory { memory_type },
+        ToolCall::ScriptTool { name, args, timeout } => {
+            let mut new_args = Vec::with_capacity(args.len());
+            for arg in args {
+                new_args.push(substitute(&arg, bindings)?);
+            }
+            ToolCall::ScriptTool { name, args: new_args, timeout }
+        }
+        ToolCall::CreateTool { name, desc, args, arg_spec, script_content } => ToolCall::CreateTool {
+            name,
+            desc,
+            args,
+            arg_spec,
+            script_content: substitute(&script_content, bindings)?,
+        },
+        ToolCall::RunScript { lang, args, timeout, script_content } => ToolCall::RunScript {
+            lang,
+            args: args.map(|a| substitute(&a, bindings)).transpose()?,
+            timeout,
+            script_content: substitute(&script_content, bindings)?,
+        },
+        ToolCall::Help { filter } => ToolCall::Help { filter: filter.map(|f| substitute(&f, bindings)).transpose()? },
+        ToolCall::Watch { paths, debounce_ms } => {
+            let mut new_paths = Vec::with_capacity(paths.len());
+            for path in paths {
+                new_paths.push(substitute(&path, bindings)?);
+            }
+            ToolCall::Watch { paths: new_paths, debounce_ms }
+        }
+        ToolCall::SetAlias { name, expansion } => ToolCall::SetAlias { name, expansion: substitute(&expansion, bindings)? },
+        ToolCall::ClearAlias { name } => ToolCall::ClearAlias { name },
+        ToolCall::Archive { paths, dest, format } => {
+            let mut new_paths = Vec::with_capacity(paths.len());
+            for path in paths {
+                new_paths.push(substitute(&path, bindings)?);
+            }
+            ToolCall::Archive { paths: new_paths, dest: substitute(&dest, bindings)?, format }
+        }
+        ToolCall::Extract { archive, dest } => ToolCall::Extract { archive: substitute(&archive, bindings)?, dest: substitute(&dest, bindings)? },
+    })
+}
+
+/// `ToolCall` variants whose execution only reads filesystem state and leaves
+/// `self.working_dir`/`self.command_processor` untouched, so a contiguous run
+/// of them can be dispatched across threads instead of one at a time. Mutating
+/// calls (`WriteFile`, `ChangeDir`, `Shell`, ...) always run serially in
+/// `execute_actions` because later ones may depend on an earlier one's effect
+/// on `self.working_dir` or the filesystem.
+fn is_read_only_call(tool_call: &ToolCall) -> bool {
+    matches!(tool_call, ToolCall::ReadFile { .. } | ToolCall::ListDir { .. })
+}
+
+/// A single worker-thread step of `PrimeSession::execute_read_only_batch`:
+/// runs one already-permission-checked `ReadFile`/`ListDir` call against
+/// `working_dir`, mirroring `execute_tool`'s output formatting for those
+/// two variants exactly so a batched result reads identically to a serial one.
+fn execute_read_only_call(tool_call: &ToolCall, working_dir: &Path, ignored_path_patterns: &[Pattern]) -> ToolExecutionResult {
+    let tool_call_str = tool_call.to_string();
+    let (success, output) = match tool_call {
+        ToolCall::ReadFile { path, lines } => {
+            let absolute_path = working_dir.join(path);
+            match crate::commands::read_file_checked(&absolute_path, *lines) {
+                Ok((content, truncated)) => {
+                    (true, if truncated { format!("{}\nNote: File content was truncated", content) } else { content })
+                }
+                Err(e) => (false, format!("Failed to read file '{}': {}", absolute_path.display(), e)),
+            }
+        }
+        ToolCall::ListDir { path } => {
+            let absolute_path = working_dir.join(path);
+            match crate::commands::list_directory_checked(&absolute_path, ignored_path_patterns) {
+                Ok(items) => (true, if items.is_empty() { "Directory is empty".to_string() } else { items.join("\n") }),
+                Err(e) => (false, format!("Failed to list directory '{}': {}", absolute_path.display(), e)),
+            }
+        }
+        other => unreachable!("execute_read_only_batch only dispatches ReadFile/ListDir, got {:?}", other),
+    };
+    ToolExecutionResult { tool_call_str, success, output, exit_code: None, terminated_by_signal: false }
+}
+
+/// Classifies a process-backed tool call's raw `(exit_code, output)` result
+/// (or spawn error) into a `(success, message, exit_code, terminated_by_signal)`
+/// tuple, matching on `status.code()` the way `ExitStatus` does: `Some(0)` is
+/// success, `Some(127)` gets a "command not found" callout so the
+/// self-correction loop can suggest `create_tool`/installing a dependency
+/// instead of blindly retrying, and the `-1` sentinel `CommandProcessor`
+/// already uses for `status.code() == None` is reported as signal-killed.
+fn classify_process_result(label: &str, result: Result<(i32, String)>) -> (bool, String, Option<i32>, bool) {
+    match result {
+        Ok((0, out)) => (true, out, Some(0), false),
+        Ok((-1, out)) => (false, format!("{} was killed by signal\nOutput:\n{}", label, out), None, true),
+        Ok((127, out)) => (false, format!("{} failed: command not found (127)\nOutput:\n{}", label, out), Some(127), false),
+        Ok((code, out)) => (false, format!("{} failed with exit code {}\nOutput:\n{}", label, code, out), Some(code), false),
+        Err(e) => (false, format!("Failed to execute {}: {}", label.to_lowercase(), e), None, false),
+    }
+}
+
+/// Resolves a `shell`/`ScriptTool` `timeout=` argument into the `Duration`
+/// `execute_command_with_timeout` expects: `None` (the LLM didn't specify
+/// one) falls back to `DEFAULT_COMMAND_TIMEOUT_SECS`, `Some(0)` is the
+/// documented opt-out (no hard timeout at all), and `Some(n)` is `n` seconds.
+fn resolve_timeout(timeout: Option<u64>) -> Option<Duration> {
+    match timeout {
+        Some(0) => None,
+        Some(secs) => Some(Duration::from_secs(secs)),
+        None => Some(Duration::from_secs(commands::DEFAULT_COMMAND_TIMEOUT_SECS)),
+    }
+}
+
 #[derive(Debug)]
 pub struct ToolExecutionResult {
     pub tool_call_str: String,
     pub success: bool,
     pub output: String,
+    pub exit_code: Option<i32>,
+    pub terminated_by_signal: bool,
+}
+
+impl ToolExecutionResult {
+    /// A short classification of how this result exited, for
+    /// `format_tool_failure_for_llm`/`format_tool_results_for_llm` to surface
+    /// alongside the raw output. `None` for non-process tool calls and clean exits.
+    fn exit_classification(&self) -> Option<String> {
+        if self.terminated_by_signal {
+            return Some("killed by signal".to_string());
+        }
+        match self.exit_code {
+            None | Some(0) => None,
+            Some(127) => Some("command not found (127)".to_string()),
+            Some(n) => Some(format!("exited with code {}", n)),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -32,13 +219,68 @@ pub struct DiscoveredTool {
     pub name: String,
     pub desc: String,
     pub args: String,
+    pub arg_spec: Vec<parser::ToolArgSpec>,
     pub path: PathBuf,
 }
 
+/// Builtin tool catalog: (name, description, usage signature, canonical
+/// argument names). Shared by `help_listing`'s human-readable dump and
+/// `PrimeSession::tool_catalog`'s structured completion/introspection surface,
+/// so the two never drift out of sync.
+const BUILTIN_TOOLS: &[(&str, &str, &str, &[&str])] = &[
+    ("shell", "Execute any shell command", "<command> [timeout=N]", &["command", "timeout"]),
+    ("cd", "Change working directory", "<path>", &["path"]),
+    ("read_file", "Read file content (with optional line range)", "<path> [lines=start-end]", &["path", "lines"]),
+    ("write_file", "Write to file (with optional append)", "<path> [append=true]", &["path", "append"]),
+    ("list_dir", "List directory contents", "<path>", &["path"]),
+    ("write_memory", "Add to long/short-term memory", "<long_term|short_term>", &["memory_type"]),
+    ("clear_memory", "Clear memory type", "<long_term|short_term>", &["memory_type"]),
+    ("create_tool", "Create a new self-extending tool script", "name=<name> desc=\"...\" args=\"...\"", &["name", "desc", "args"]),
+    (
+        "run_script",
+        "Write a temp-file script and run it with a matching interpreter",
+        "lang=<python|node|bash|pwsh|ruby|php> [args=\"...\"] [timeout=30]",
+        &["lang", "args", "timeout"],
+    ),
+    ("help", "List available tools, optionally filtered by substring", "[filter]", &["filter"]),
+    (
+        "watch",
+        "Watch paths for changes; reported on a future turn as a synthetic tool result",
+        "<path>... [debounce_ms=200]",
+        &["paths", "debounce_ms"],
+    ),
+    ("alias", "Define a short name that expands to a fuller tool invocation", "<name> = <expansion>", &["name", "expansion"]),
+    ("unalias", "Remove a registered alias", "<name>", &["name"]),
+    (
+        "archive",
+        "Package files/directories into a .tar.xz or .tar.gz archive",
+        "<path>... dest=<dest> [format=xz|gz]",
+        &["paths", "dest", "format"],
+    ),
+    ("extract", "Extract an archive into a destination directory", "<archive> dest=<dest>", &["archive", "dest"]),
+];
+
+/// One entry in `PrimeSession::tool_catalog`: a builtin, `./prime/` discovered
+/// script, plugin, live plugin, or alias normalized to a common name/kind/
+/// description/argument-names shape, regardless of which registry it came
+/// from. The structured counterpart to `help_listing`'s formatted text.
+#[derive(Debug, Clone)]
+pub struct ToolDescription {
+    pub name: String,
+    pub kind: &'static str,
+    pub desc: String,
+    pub arg_names: Vec<String>,
+}
+
 impl fmt::Display for ToolCall {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ToolCall::Shell { command } => write!(f, "shell: {}", command),
+            ToolCall::Shell { command, timeout } => {
+                match timeout {
+                    Some(t) => write!(f, "shell: {} timeout={}", command, t),
+                    None => write!(f, "shell: {}", command),
+                }
+            }
             ToolCall::ReadFile { path, lines } => {
                 if let Some((s, e)) = lines {
                     write!(f, "read_file: {} lines={}-{}", path, s, e)
@@ -65,8 +307,13 @@ impl fmt::Display for ToolCall {
                 write!(f, "write_memory: {} (content: \"{}\")", memory_type, content_snip)
             }
             ToolCall::ClearMemory { memory_type } => write!(f, "clear_memory: {}", memory_type),
-            ToolCall::ScriptTool { name, args } => write!(f, "{}: {}", name, args.join(" ")),
-            ToolCall::CreateTool { name, desc, args, script_content } => {
+            ToolCall::ScriptTool { name, args, timeout } => {
+                match timeout {
+                    Some(t) => write!(f, "{}: {} timeout={}", name, args.join(" "), t),
+                    None => write!(f, "{}: {}", name, args.join(" ")),
+                }
+            }
+            ToolCall::CreateTool { name, desc, args, script_content, .. } => {
                 let content_snip = if script_content.len() > 30 {
                     format!("{}...", &script_content[..30].replace('\n', " "))
                 } else {
@@ -74,10 +321,55 @@ impl fmt::Display for ToolCall {
                 };
                 write!(f, "create_tool: name={} desc=\"{}\" args=\"{}\" (content: \"{}\")", name, desc, args, content_snip)
             }
+            ToolCall::RunScript { lang, args, timeout, script_content } => {
+                let content_snip = if script_content.len() > 30 {
+                    format!("{}...", &script_content[..30].replace('\n', " "))
+                } else {
+                    script_content.replace('\n', " ")
+                };
+                write!(
+                    f,
+                    "run_script: lang={}{}{} (content: \"{}\")",
+                    lang,
+                    args.as_ref().map(|a| format!(" args=\"{}\"", a)).unwrap_or_default(),
+                    timeout.map(|t| format!(" timeout={}", t)).unwrap_or_default(),
+                    content_snip
+                )
+            }
+            ToolCall::Help { filter } => write!(f, "help: {}", filter.as_deref().unwrap_or("")),
+            ToolCall::Watch { paths, debounce_ms } => {
+                write!(f, "watch: {}{}", paths.join(" "), debounce_ms.map(|d| format!(" debounce_ms={}", d)).unwrap_or_default())
+            }
+            ToolCall::SetAlias { name, expansion } => write!(f, "alias: {} = {}", name, expansion),
+            ToolCall::ClearAlias { name } => write!(f, "unalias: {}", name),
+            ToolCall::Archive { paths, dest, format } => write!(f, "archive: {} dest={} format={}", paths.join(" "), dest, format),
+            ToolCall::Extract { archive, dest } => write!(f, "extract: {} dest={}", archive, dest),
         }
     }
 }
 
+/// Whether `process_input` is driven by an interactive terminal (stdin
+/// confirmations, a 2s executing delay so the user can see the plan) or a
+/// non-interactive batch run (`run_script_file`), where both of those must be
+/// resolved without a human at the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionMode {
+    Interactive,
+    Headless(ConfirmPolicy),
+}
+
+/// How a headless run resolves a destructive-action confirmation that would
+/// otherwise block on stdin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmPolicy {
+    /// Always answer "y" (`--yes`).
+    AlwaysYes,
+    /// Always answer "N" (`--deny`).
+    AlwaysDeny,
+    /// Abort the whole script the first time a destructive action is proposed.
+    AbortOnDestructive,
+}
+
 pub struct PrimeSession {
     pub base_dir: PathBuf,
     pub session_id: String,
@@ -86,7 +378,16 @@ pub struct PrimeSession {
     pub command_processor: CommandProcessor,
     pub memory_manager: MemoryManager,
     pub working_dir: PathBuf,
+    pub mode: SessionMode,
     pub discovered_tools: Vec<DiscoveredTool>,
+    pub plugin_manager: PluginManager,
+    pub live_plugin_manager: LivePluginManager,
+    /// Watches registered via `ToolCall::Watch`, each snapshotting its paths
+    /// as absolute at registration time so a later `ChangeDir` can't move them.
+    active_watches: Vec<watch::ActiveWatch>,
+    /// Short names that expand to a fuller tool invocation, persisted under
+    /// `./prime/` via `ToolCall::SetAlias`/`ClearAlias`.
+    aliases: AliasMap,
 }
 
 impl PrimeSession {
@@ -99,6 +400,9 @@ impl PrimeSession {
         let memory_manager = MemoryManager::new(memory_dir)?;
         let working_dir = std::env::current_dir().context("Failed to get current working directory")?;
         let discovered_tools = Self::discover_tools(&working_dir)?;
+        let plugin_manager = PluginManager::discover(&base_dir.join("plugins"));
+        let live_plugin_manager = LivePluginManager::discover(&working_dir.join("prime"));
+        let aliases = AliasMap::load(&working_dir)?;
         Ok(Self {
             base_dir,
             session_id,
@@ -107,7 +411,12 @@ impl PrimeSession {
             command_processor: CommandProcessor::new(),
             memory_manager,
             working_dir,
+            mode: SessionMode::Interactive,
             discovered_tools,
+            plugin_manager,
+            live_plugin_manager,
+            active_watches: Vec::new(),
+            aliases,
         })
     }
 
@@ -146,7 +455,8 @@ impl PrimeSession {
                     if let Some(header_str) = header_found {
                         let (parsed_name, parsed_desc, parsed_args) = Self::parse_tool_header(&header_str)?;
                         if parsed_name == name_stem {
-                            tools.push(DiscoveredTool { name: parsed_name, desc: parsed_desc, args: parsed_args, path });
+                            let arg_spec = parser::parse_tool_arg_spec(&parsed_args).unwrap_or_default();
+                            tools.push(DiscoveredTool { name: parsed_name, desc: parsed_desc, args: parsed_args, arg_spec, path });
                         }
                     }
                 }
@@ -210,23 +520,69 @@ impl PrimeSession {
 
     pub fn reload_tools(&mut self) -> Result<()> {
         self.discovered_tools = Self::discover_tools(&self.working_dir)?;
+        self.live_plugin_manager = LivePluginManager::discover(&self.working_dir.join("prime"));
+        self.aliases = AliasMap::load(&self.working_dir)?;
         Ok(())
     }
 
+    /// If `tool_call` is a `ScriptTool` whose name matches a registered alias,
+    /// expands it by splicing the alias's expansion text with any extra args
+    /// the caller passed and re-parsing it as a single `primeactions` line
+    /// through the normal parser, so alias expansion reuses all existing
+    /// parsing logic instead of duplicating it. Non-matching calls pass through
+    /// unchanged.
+    fn expand_alias(&self, tool_call: ToolCall) -> Result<ToolCall, String> {
+        let ToolCall::ScriptTool { name, args, timeout } = &tool_call else { return Ok(tool_call) };
+        let Some(expansion) = self.aliases.get(name) else { return Ok(tool_call) };
+        let mut line = expansion.to_string();
+        if !args.is_empty() {
+            line.push(' ');
+            line.push_str(&args.join(" "));
+        }
+        let block = format!("```primeactions\n{}\n```", line);
+        let parsed = parser::parse_llm_response(&block).map_err(|e| format!("Failed to expand alias '{}': {}", name, e))?;
+        let mut steps = parsed.steps.into_iter();
+        let (Some(step), None) = (steps.next(), steps.next()) else {
+            return Err(format!("Alias '{}' must expand to exactly one tool call", name));
+        };
+        Ok(match step.tool_call {
+            ToolCall::Shell { command, timeout: expansion_timeout } => ToolCall::Shell { command, timeout: timeout.or(expansion_timeout) },
+            ToolCall::ScriptTool { name: expanded_name, args: expanded_args, timeout: expansion_timeout } => {
+                ToolCall::ScriptTool { name: expanded_name, args: expanded_args, timeout: timeout.or(expansion_timeout) }
+            }
+            other => other,
+        })
+    }
+
+    /// Validates `args` against the typed signature stored in the named tool's
+    /// header. Returns `Some(usage error)` on mismatch, `None` when the args are
+    /// acceptable or the tool can't be found (script-level checks still apply).
+    fn validate_script_args(&self, name: &str, args: &[String]) -> Option<String> {
+        let tool = self.discovered_tools.iter().find(|t| t.name == name)?;
+        match parser::validate_tool_args(&tool.arg_spec, args) {
+            Ok(()) => None,
+            Err(e) => Some(format!("{}\nUsage: {} {}", e, name, parser::render_tool_args(&tool.arg_spec))),
+        }
+    }
+
     pub fn is_tool_destructive(&self, tool_call: &ToolCall) -> bool {
         match tool_call {
-            ToolCall::Shell { command } => {
-                self.command_processor.is_command_destructive(command)
+            ToolCall::Shell { command, .. } => {
+                self.command_processor.is_ask_me_before_command(command)
             }
-            ToolCall::ScriptTool { name, args } => {
+            ToolCall::ScriptTool { name, args, .. } => {
                 let ext = if cfg!(target_os = "windows") { "ps1" } else { "sh" };
                 let mut full_cmd = format!("./prime/tool_{}.{}", name, ext);
                 if !args.is_empty() {
                     full_cmd.push_str(&format!(" {}", args.join(" ")));
                 }
-                self.command_processor.is_command_destructive(&full_cmd)
+                self.command_processor.is_ask_me_before_command(&full_cmd)
+            }
+            ToolCall::RunScript { script_content, .. } => {
+                self.command_processor.is_ask_me_before_command(script_content)
             }
             ToolCall::CreateTool { .. } => false,
+            ToolCall::SetAlias { .. } | ToolCall::ClearAlias { .. } => false,
             _ => false,
         }
     }
@@ -238,13 +594,14 @@ impl PrimeSession {
         let mut tool_turn_count = 0;
         let mut has_displayed_actions = false;
         loop {
+            self.report_watch_changes()?;
             if tool_turn_count >= MAX_CONSECUTIVE_TOOL_TURNS {
                 println!("{}", "Reached maximum tool execution turns. The session might be in a loop. Please try a new prompt.".red());
                 break;
             }
             let response_text = self.generate_prime_response().await?;
             let parsed = parser::parse_llm_response(&response_text)?;
-            if parsed.tool_calls.is_empty() {
+            if parsed.steps.is_empty() {
                 if !parsed.natural_language.is_empty() {
                     if has_displayed_actions {
                         println!();
@@ -272,37 +629,88 @@ impl PrimeSession {
             }
             println!();
             println!("{}", "┏━ actions".yellow());
-            for tool in &parsed.tool_calls {
+            for step in &parsed.steps {
+                let tool = &step.tool_call;
+                let bind_prefix = step.bind.as_deref().map(|n| format!("${} = ", n)).unwrap_or_default();
                 match tool {
-                    ToolCall::Shell { command } => println!("{}", format!("┃ {}", command).yellow()),
+                    ToolCall::Shell { command, timeout } => {
+                        let timeout_suffix = timeout.map(|t| format!(" timeout={}", t)).unwrap_or_default();
+                        println!("{}", format!("┃ {}{}{}", bind_prefix, command, timeout_suffix).yellow())
+                    }
                     ToolCall::ReadFile { path, lines } => {
                         if let Some((start, end)) = lines {
-                            println!("{}", format!("┃ read_file: {} lines={}-{}", path, start, end).yellow());
+                            println!("{}", format!("┃ {}read_file: {} lines={}-{}", bind_prefix, path, start, end).yellow());
                         } else {
-                            println!("{}", format!("┃ read_file: {}", path).yellow());
+                            println!("{}", format!("┃ {}read_file: {}", bind_prefix, path).yellow());
                         }
                     }
-                    ToolCall::WriteFile { path, .. } => println!("{}", format!("┃ write_file: {}", path).yellow()),
-                    ToolCall::ListDir { path } => println!("{}", format!("┃ list_dir: {}", path).yellow()),
-                    ToolCall::ChangeDir { path } => println!("{}", format!("┃ cd: {}", path).yellow()),
-                    ToolCall::WriteMemory { memory_type, .. } => println!("{}", format!("┃ write_memory: {}", memory_type).yellow()),
-                    ToolCall::ClearMemory { memory_type } => println!("{}", format!("┃ clear_memory: {}", memory_type).yellow()),
-                    ToolCall::ScriptTool { name, args } => println!("{}", format!("┃ {}: {}", name, args.join(" ")).yellow()),
-                    ToolCall::CreateTool { name, desc, args, .. } => println!("{}", format!("┃ create_tool: name={} desc=\"{}\" args=\"{}\"", name, desc, args).yellow()),
+                    ToolCall::WriteFile { path, .. } => println!("{}", format!("┃ {}write_file: {}", bind_prefix, path).yellow()),
+                    ToolCall::ListDir { path } => println!("{}", format!("┃ {}list_dir: {}", bind_prefix, path).yellow()),
+                    ToolCall::ChangeDir { path } => println!("{}", format!("┃ {}cd: {}", bind_prefix, path).yellow()),
+                    ToolCall::WriteMemory { memory_type, .. } => println!("{}", format!("┃ {}write_memory: {}", bind_prefix, memory_type).yellow()),
+                    ToolCall::ClearMemory { memory_type } => println!("{}", format!("┃ {}clear_memory: {}", bind_prefix, memory_type).yellow()),
+                    ToolCall::ScriptTool { name, args, timeout } => {
+                        let timeout_suffix = timeout.map(|t| format!(" timeout={}", t)).unwrap_or_default();
+                        println!("{}", format!("┃ {}{}: {}{}", bind_prefix, name, args.join(" "), timeout_suffix).yellow())
+                    }
+                    ToolCall::CreateTool { name, desc, args, .. } => println!("{}", format!("┃ {}create_tool: name={} desc=\"{}\" args=\"{}\"", bind_prefix, name, desc, args).yellow()),
+                    ToolCall::RunScript { lang, args, .. } => {
+                        let args_suffix = args.as_deref().map(|a| format!(" args=\"{}\"", a)).unwrap_or_default();
+                        println!("{}", format!("┃ {}run_script: lang={}{}", bind_prefix, lang, args_suffix).yellow())
+                    }
+                    ToolCall::Help { filter } => {
+                        println!("{}", format!("┃ {}help: {}", bind_prefix, filter.as_deref().unwrap_or("")).yellow())
+                    }
+                    ToolCall::Watch { paths, debounce_ms } => {
+                        let debounce_suffix = debounce_ms.map(|d| format!(" debounce_ms={}", d)).unwrap_or_default();
+                        println!("{}", format!("┃ {}watch: {}{}", bind_prefix, paths.join(" "), debounce_suffix).yellow())
+                    }
+                    ToolCall::SetAlias { name, expansion } => println!("{}", format!("┃ {}alias: {} = {}", bind_prefix, name, expansion).yellow()),
+                    ToolCall::ClearAlias { name } => println!("{}", format!("┃ {}unalias: {}", bind_prefix, name).yellow()),
+                    ToolCall::Archive { paths, dest, format } => {
+                        println!("{}", format!("┃ {}archive: {} dest={} format={}", bind_prefix, paths.join(" "), dest, format).yellow())
+                    }
+                    ToolCall::Extract { archive, dest } => println!("{}", format!("┃ {}extract: {} dest={}", bind_prefix, archive, dest).yellow()),
                 }
             }
-            let is_destructive = parsed.tool_calls.iter().any(|tc| self.is_tool_destructive(tc));
-            let should_execute = if is_destructive {
+            let is_destructive = parsed.steps.iter().any(|s| self.is_tool_destructive(&s.tool_call));
+            let (should_execute, elevate) = if is_destructive {
                 println!("{}", "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ destructive ━━━━━".red());
-                print!("{}", "Execute? (y/N): ".red());
-                io::stdout().flush().context("Failed to flush stdout")?;
-                let mut confirmation = String::new();
-                io::stdin().read_line(&mut confirmation).context("Failed to read user input")?;
-                confirmation.trim().eq_ignore_ascii_case("y")
+                match self.mode {
+                    SessionMode::Interactive => {
+                        print!("{}", "Execute? (y/N/e=elevate): ".red());
+                        io::stdout().flush().context("Failed to flush stdout")?;
+                        let mut confirmation = String::new();
+                        io::stdin().read_line(&mut confirmation).context("Failed to read user input")?;
+                        match confirmation.trim().to_lowercase().as_str() {
+                            "y" => (true, false),
+                            "e" | "elevate" => (true, true),
+                            _ => (false, false),
+                        }
+                    }
+                    SessionMode::Headless(ConfirmPolicy::AlwaysYes) => {
+                        println!("{}", "Auto-approved by --yes policy.".yellow());
+                        (true, false)
+                    }
+                    SessionMode::Headless(ConfirmPolicy::AlwaysDeny) => {
+                        println!("{}", "Auto-denied by --deny policy.".yellow());
+                        (false, false)
+                    }
+                    SessionMode::Headless(ConfirmPolicy::AbortOnDestructive) => {
+                        return Err(anyhow!("Headless run aborted: a destructive action requires confirmation"));
+                    }
+                }
             } else {
-                println!("{}", "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ executing in 2s ━━━━━".yellow());
-                std::thread::sleep(std::time::Duration::from_secs(2));
-                true
+                match self.mode {
+                    SessionMode::Interactive => {
+                        println!("{}", "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ executing in 2s ━━━━━".yellow());
+                        std::thread::sleep(std::time::Duration::from_secs(2));
+                    }
+                    SessionMode::Headless(_) => {
+                        println!("{}", "┗━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━ executing ━━━━━".yellow());
+                    }
+                }
+                (true, false)
             };
             if !should_execute {
                 println!();
@@ -312,7 +720,7 @@ impl PrimeSession {
                 break;
             }
             has_displayed_actions = true;
-            match self.execute_actions(parsed.tool_calls).await {
+            match self.execute_actions(parsed.steps, elevate).await {
                 Ok(successful_results) => {
                     let results_prompt = self.format_tool_results_for_llm(&successful_results)?;
                     self.save_log("Tool Results", &results_prompt)?;
@@ -329,14 +737,146 @@ impl PrimeSession {
         Ok(())
     }
 
+    /// Runs a `.prime` script file — blank-line-separated directives, each either
+    /// plain text fed to `process_input` as a user prompt, or a literal
+    /// ```` ```primeactions ```` block executed directly against the tool
+    /// machinery without round-tripping through the LLM — in sequence against
+    /// this session. For the duration of the run, destructive confirmations are
+    /// resolved by `policy` instead of stdin and the interactive executing delay
+    /// is skipped; the session's prior mode is restored before returning.
+    pub async fn run_script_file(&mut self, path: &Path, policy: ConfirmPolicy) -> Result<()> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script file: {}", path.display()))?;
+        let previous_mode = self.mode;
+        self.mode = SessionMode::Headless(policy);
+
+        let mut result = Ok(());
+        for directive in content.split("\n\n") {
+            let directive = directive.trim();
+            if directive.is_empty() || directive.starts_with('#') {
+                continue;
+            }
+            let step_result = if directive.contains("```primeactions") {
+                self.run_inline_actions(directive).await
+            } else {
+                self.process_input(directive).await
+            };
+            if let Err(e) = step_result {
+                result = Err(e);
+                break;
+            }
+        }
+
+        self.mode = previous_mode;
+        result
+    }
+
+    /// Parses and executes a literal `primeactions` block from a `.prime` script
+    /// file directly, honoring the active `ConfirmPolicy` for any destructive step
+    /// instead of blocking on a confirmation prompt that has no one to answer it.
+    async fn run_inline_actions(&mut self, block: &str) -> Result<()> {
+        let parsed = parser::parse_llm_response(block)?;
+        if parsed.steps.is_empty() {
+            return Ok(());
+        }
+        let is_destructive = parsed.steps.iter().any(|s| self.is_tool_destructive(&s.tool_call));
+        if is_destructive {
+            match self.mode {
+                SessionMode::Headless(ConfirmPolicy::AlwaysDeny) => {
+                    self.save_log("System", "Destructive inline action denied by --deny policy.")?;
+                    return Ok(());
+                }
+                SessionMode::Headless(ConfirmPolicy::AbortOnDestructive) => {
+                    return Err(anyhow!("Headless run aborted: a destructive inline action requires confirmation"));
+                }
+                _ => {}
+            }
+        }
+        match self.execute_actions(parsed.steps, false).await {
+            Ok(successful_results) => {
+                let results_prompt = self.format_tool_results_for_llm(&successful_results)?;
+                self.save_log("Tool Results", &results_prompt)?;
+            }
+            Err(failed_result) => {
+                let error_prompt = self.format_tool_failure_for_llm(&failed_result)?;
+                self.save_log("Tool Failure", &error_prompt)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drains every `ToolCall::Watch` registration for paths that changed
+    /// since the last check and, if any did, logs them as a synthetic "Watch
+    /// Event" entry (picked up by `get_history` the same way a real tool
+    /// result is) so the LLM sees the change on its next turn without the
+    /// user having to ask.
+    fn report_watch_changes(&self) -> Result<()> {
+        let mut changed_paths = Vec::new();
+        for active_watch in &self.active_watches {
+            changed_paths.extend(active_watch.drain_changes());
+        }
+        if changed_paths.is_empty() {
+            return Ok(());
+        }
+        changed_paths.sort();
+        changed_paths.dedup();
+        let summary = changed_paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n");
+        println!();
+        println!("{}", "┃ Watched paths changed:".yellow());
+        for line in summary.lines() {
+            println!("{}", format!("│ {}", line).dim());
+        }
+        self.save_log("Watch Event", &format!("The following watched paths changed:\n{}", summary))
+    }
+
+    /// Returns the `.gz` sibling of `session_log_path` that the log is moved
+    /// to once it crosses `LOG_COMPRESSION_THRESHOLD_BYTES`.
+    fn compressed_log_path(&self) -> PathBuf {
+        let file_name = format!("{}.gz", self.session_log_path.file_name().unwrap_or_default().to_string_lossy());
+        self.session_log_path.with_file_name(file_name)
+    }
+
+    /// Reads the session log, transparently decompressing it if it has
+    /// already been archived to `<log>.gz`.
+    fn read_log_content(&self) -> Result<String> {
+        let gz_path = self.compressed_log_path();
+        if gz_path.exists() {
+            let file = fs::File::open(&gz_path).with_context(|| format!("Could not read session log file: {}", gz_path.display()))?;
+            let mut content = String::new();
+            GzDecoder::new(file)
+                .read_to_string(&mut content)
+                .with_context(|| format!("Could not decompress session log file: {}", gz_path.display()))?;
+            Ok(content)
+        } else {
+            Ok(fs::read_to_string(&self.session_log_path).unwrap_or_default())
+        }
+    }
+
+    /// Writes the full session log back out, switching to gzip compression
+    /// once `content` crosses `LOG_COMPRESSION_THRESHOLD_BYTES` (or staying
+    /// compressed if it already was), and removing the stale plain/compressed
+    /// sibling so only one copy of the log exists on disk.
+    fn write_log_content(&self, content: &str) -> Result<()> {
+        let gz_path = self.compressed_log_path();
+        if content.len() >= LOG_COMPRESSION_THRESHOLD_BYTES || gz_path.exists() {
+            let file = fs::File::create(&gz_path).with_context(|| format!("Could not write session log file: {}", gz_path.display()))?;
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(content.as_bytes())?;
+            encoder.finish()?;
+            if self.session_log_path.exists() {
+                fs::remove_file(&self.session_log_path).ok();
+            }
+        } else {
+            fs::write(&self.session_log_path, content).with_context(|| format!("Could not write session log file: {}", self.session_log_path.display()))?;
+        }
+        Ok(())
+    }
+
     fn save_log(&self, title: &str, content: &str) -> Result<()> {
-        let mut file = OpenOptions::new().create(true).append(true).open(&self.session_log_path)?;
         let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S");
-        writeln!(file, "\n## {} ({})", title, timestamp)?;
-        writeln!(file, "```")?;
-        writeln!(file, "{}", content.trim())?;
-        writeln!(file, "```")?;
-        Ok(())
+        let mut log = self.read_log_content()?;
+        log.push_str(&format!("\n## {} ({})\n```\n{}\n```\n", title, timestamp, content.trim()));
+        self.write_log_content(&log)
     }
 
     async fn generate_prime_response(&mut self) -> Result<String> {
@@ -431,8 +971,9 @@ Use create_tool proactively to build specialized tools for recurring or complex
         let mut tools_section = String::new();
         tools_section.push_str(r#"
 **AVAILABLE TOOLS**
-1. `shell: <command>`
+1. `shell: <command> [timeout=N]`
     - Executes a shell command in the current working directory.
+    - A hard timeout (seconds) applies by default so a hung command can't block you forever; pass `timeout=N` to change it, or `timeout=0` to run with no limit.
     - Example: `shell: ls -l`
 2. `cd: <path>`
     - Changes the current working directory. The new directory persists for all future commands.
@@ -486,25 +1027,54 @@ Use create_tool proactively to build specialized tools for recurring or complex
       Get-ChildItem -Path $path -Recurse -ErrorAction SilentlyContinue | Select-String -Pattern $pattern | ForEach-Object { $_.Line }
       EOF_PRIME
       ```
+9. `run_script: lang=<python|node|bash|pwsh|ruby|php> [args="..."] [timeout=30]`
+    - Writes the script body to a temp file and runs it with the matching interpreter, instead of inlining everything into `shell`.
+    - The script content follows on new lines, terminated by `EOF_PRIME`.
+    - `timeout` (seconds) kills the script and reports a timed-out failure if it runs longer; omit it to run with no limit.
+    - Example:
+      ```primeactions
+      run_script: lang=python args="--verbose" timeout=10
+      import sys
+      print("args:", sys.argv[1:])
+      EOF_PRIME
+      ```
+10. `alias: <name> = <expansion>`
+    - Registers a short name that expands to a fuller tool invocation (e.g. `gs = shell: git status`), persisted under ./prime/ so it survives restarts.
+    - Expansion happens before the underlying tool runs; any extra args you pass after the alias name are appended to the expansion.
+    - Example: `alias: gs = shell: git status`
+11. `unalias: <name>`
+    - Removes a registered alias.
+    - Example: `unalias: gs`
+12. `archive: <path>... dest=<dest> [format=xz|gz]`
+    - Packages one or more files/directories into a `.tar.xz` (default) or `.tar.gz` archive at `dest`, relative to the working directory.
+    - Example: `archive: build/ dest=build.tar.xz format=xz`
+13. `extract: <archive> dest=<dest>`
+    - Extracts `archive` into the `dest` directory, creating it if needed.
+    - Example: `extract: build.tar.xz dest=build/`
 "#);
         for (i, tool) in self.discovered_tools.iter().enumerate() {
-            let num = 9 + i;
-            let arg_example = if !tool.args.is_empty() {
-                let arg_parts: Vec<&str> = tool.args.split_whitespace().collect();
-                if arg_parts.len() >= 2 {
-                    format!(" (e.g., {}: {} {})", tool.name, arg_parts[0], arg_parts[1])
-                } else if !arg_parts.is_empty() {
-                    format!(" (e.g., {}: {})", tool.name, arg_parts[0])
-                } else {
-                    String::new()
-                }
+            let num = 14 + i;
+            let arg_example = if !tool.arg_spec.is_empty() {
+                format!(" (usage: {}: {})", tool.name, parser::render_tool_args(&tool.arg_spec))
             } else {
                 String::new()
             };
             tools_section.push_str(&format!("\n{}. `{}` - {}{}", num, tool.name, tool.desc, arg_example));
         }
         if !self.discovered_tools.is_empty() {
-            tools_section.push_str("\nFor custom tools, use `tool_name: arg1 arg2` (space-separated).");
+            tools_section.push_str("\nFor custom tools, use `tool_name: arg1 arg2 [timeout=N]` (space-separated; same timeout/opt-out as `shell`).");
+        }
+        for (i, tool) in self.plugin_manager.tools.iter().enumerate() {
+            let num = 14 + self.discovered_tools.len() + i;
+            tools_section.push_str(&format!("\n{}. `{}` - {} (plugin)", num, tool.name, tool.desc));
+        }
+        for (i, plugin) in self.live_plugin_manager.plugins.iter().enumerate() {
+            let num = 14 + self.discovered_tools.len() + self.plugin_manager.tools.len() + i;
+            tools_section.push_str(&format!("\n{}. `{}` - {} (live plugin)", num, plugin.name, plugin.desc));
+        }
+        for (i, (name, expansion)) in self.aliases.iter().enumerate() {
+            let num = 14 + self.discovered_tools.len() + self.plugin_manager.tools.len() + self.live_plugin_manager.plugins.len() + i;
+            tools_section.push_str(&format!("\n{}. `{}` - alias, expands to: {}", num, name, expansion));
         }
         let technical_prompt = format!(
             r#"
@@ -540,16 +1110,51 @@ Now, begin.
 
     pub async fn execute_actions(
         &mut self,
-        tool_calls: Vec<ToolCall>,
+        steps: Vec<Step>,
+        elevate: bool,
     ) -> Result<Vec<ToolExecutionResult>, ToolExecutionResult> {
         let start_time = std::time::Instant::now();
         let mut all_results = Vec::new();
-        for tool_call in tool_calls.into_iter() {
-            let result = self.execute_tool(tool_call).await;
+        let mut bindings: HashMap<String, String> = HashMap::new();
+        let mut i = 0;
+        while i < steps.len() {
+            // A bound step's output feeds later `${name}` substitutions, so it
+            // can't be folded into a parallel batch with steps that follow it.
+            let mut j = i + 1;
+            while j < steps.len() && steps[j].bind.is_none() && is_read_only_call(&steps[j].tool_call) {
+                j += 1;
+            }
+            if steps[i].bind.is_none() && is_read_only_call(&steps[i].tool_call) && j - i >= 2 {
+                match self.execute_read_only_batch(&steps[i..j], &bindings) {
+                    Ok(results) => all_results.extend(results),
+                    Err(result) => return Err(result),
+                }
+                i = j;
+                continue;
+            }
+
+            let step = &steps[i];
+            let tool_call = match substitute_tool_call(step.tool_call.clone(), &bindings) {
+                Ok(tc) => tc,
+                Err(msg) => {
+                    return Err(ToolExecutionResult {
+                        tool_call_str: msg.clone(),
+                        success: false,
+                        output: msg,
+                        exit_code: None,
+                        terminated_by_signal: false,
+                    })
+                }
+            };
+            let result = self.execute_tool(tool_call, elevate).await;
+            if let Some(name) = &step.bind {
+                bindings.insert(name.clone(), result.output.trim().to_string());
+            }
             if !result.success {
                 return Err(result);
             }
             all_results.push(result);
+            i += 1;
         }
         let duration = start_time.elapsed();
         let duration_str = format!("{:.1}s", duration.as_secs_f32());
@@ -557,84 +1162,177 @@ Now, begin.
         Ok(all_results)
     }
 
-    async fn execute_tool(&mut self, tool_call: ToolCall) -> ToolExecutionResult {
+    /// Runs a contiguous batch of unbound `ReadFile`/`ListDir` steps
+    /// concurrently, one thread per call, then reassembles results in their
+    /// original order so bindings and `format_tool_results_for_llm`'s `id="n"`
+    /// numbering stay stable. `CommandProcessor` isn't `Sync` (it wraps a
+    /// sqlite `Connection`) and `ensure_read_permission` may block on an
+    /// interactive "ask me before" prompt, so permissions for every path are
+    /// resolved one at a time on the main thread first; the worker threads
+    /// then only touch the already-permitted filesystem and a shared borrow
+    /// of the processor's ignored-path patterns.
+    fn execute_read_only_batch(
+        &mut self,
+        steps: &[Step],
+        bindings: &HashMap<String, String>,
+    ) -> Result<Vec<ToolExecutionResult>, ToolExecutionResult> {
+        let mut substituted = Vec::with_capacity(steps.len());
+        for step in steps {
+            match substitute_tool_call(step.tool_call.clone(), bindings) {
+                Ok(tc) => substituted.push(tc),
+                Err(msg) => {
+                    return Err(ToolExecutionResult {
+                        tool_call_str: msg.clone(),
+                        success: false,
+                        output: msg,
+                        exit_code: None,
+                        terminated_by_signal: false,
+                    })
+                }
+            }
+        }
+
+        for tool_call in &substituted {
+            if let ToolCall::ReadFile { path, .. } = tool_call {
+                let absolute_path = self.working_dir.join(path);
+                if let Err(e) = self.command_processor.ensure_read_permission(&absolute_path) {
+                    return Err(ToolExecutionResult {
+                        tool_call_str: tool_call.to_string(),
+                        success: false,
+                        output: format!("Failed to read file '{}': {}", absolute_path.display(), e),
+                        exit_code: None,
+                        terminated_by_signal: false,
+                    });
+                }
+            }
+        }
+
+        let working_dir = self.working_dir.clone();
+        let ignored_path_patterns = self.command_processor.ignored_path_patterns();
+        let results: Vec<ToolExecutionResult> = std::thread::scope(|scope| {
+            let handles: Vec<_> = substituted
+                .iter()
+                .map(|tool_call| {
+                    let working_dir = &working_dir;
+                    scope.spawn(move || execute_read_only_call(tool_call, working_dir, ignored_path_patterns))
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("read-only worker thread panicked")).collect()
+        });
+
+        let mut ok_results = Vec::with_capacity(results.len());
+        for result in results {
+            if !result.output.trim().is_empty() {
+                for line in result.output.trim().lines() {
+                    println!("{}", format!("│ {}", line).dim());
+                }
+            }
+            if !result.success {
+                return Err(result);
+            }
+            ok_results.push(result);
+        }
+        Ok(ok_results)
+    }
+
+    async fn execute_tool(&mut self, tool_call: ToolCall, elevate: bool) -> ToolExecutionResult {
+        let tool_call = match self.expand_alias(tool_call) {
+            Ok(tc) => tc,
+            Err(msg) => {
+                return ToolExecutionResult { tool_call_str: msg.clone(), success: false, output: msg, exit_code: None, terminated_by_signal: false };
+            }
+        };
         let tool_call_str = tool_call.to_string();
-        let (success, output) = match tool_call {
+        let (success, output, exit_code, terminated_by_signal) = match tool_call {
             ToolCall::ChangeDir { path } => {
                 let new_path = self.working_dir.join(&path);
                 if new_path.is_dir() {
                     match new_path.canonicalize() {
                         Ok(canonical_path) => {
                             self.working_dir = canonical_path;
-                            (true, format!("Changed working directory to {}", self.working_dir.display()))
+                            (true, format!("Changed working directory to {}", self.working_dir.display()), None, false)
                         }
-                        Err(e) => (false, format!("Failed to canonicalize path '{}': {}", new_path.display(), e)),
+                        Err(e) => (false, format!("Failed to canonicalize path '{}': {}", new_path.display(), e), None, false),
                     }
                 } else {
-                    (false, format!("Directory not found: {}", new_path.display()))
+                    (false, format!("Directory not found: {}", new_path.display()), None, false)
                 }
             }
-            ToolCall::Shell { command } => {
-                match self.command_processor.execute_command(&command, Some(&self.working_dir)) {
-                    Ok((0, out)) => (true, out),
-                    Ok((code, out)) => {
-                        if code == -1 { (false, out) } else { (false, format!("Command failed with exit code {}\nOutput:\n{}", code, out)) }
-                    }
-                    Err(e) => (false, format!("Failed to execute command: {}", e)),
-                }
+            ToolCall::Shell { command, timeout } => {
+                let result = match resolve_timeout(timeout) {
+                    Some(timeout) => self.command_processor.execute_command_with_timeout(&command, Some(&self.working_dir), timeout, elevate),
+                    None if elevate => self.command_processor.execute_command_elevated(&command, Some(&self.working_dir)),
+                    None => self.command_processor.execute_command(&command, Some(&self.working_dir)),
+                };
+                classify_process_result("Command", result)
             }
             ToolCall::ReadFile { path, lines } => {
                 let absolute_path = self.working_dir.join(&path);
                 match self.command_processor.read_file_to_string_with_limit(&absolute_path, lines) {
                     Ok((content, truncated)) => {
                         let result = if truncated { format!("{}\nNote: File content was truncated", content) } else { content };
-                        (true, result)
+                        (true, result, None, false)
                     }
-                    Err(e) => (false, format!("Failed to read file '{}': {}", absolute_path.display(), e)),
+                    Err(e) => (false, format!("Failed to read file '{}': {}", absolute_path.display(), e), None, false),
                 }
             }
             ToolCall::WriteFile { path, content, append } => {
                 let absolute_path = self.working_dir.join(&path);
                 match self.command_processor.write_file_to_path(&absolute_path, &content, append) {
-                    Ok(()) => (true, format!("Successfully wrote to {}", absolute_path.display())),
-                    Err(e) => (false, format!("Failed to write file '{}': {}", absolute_path.display(), e)),
+                    Ok(()) => (true, format!("Successfully wrote to {}", absolute_path.display()), None, false),
+                    Err(e) => (false, format!("Failed to write file '{}': {}", absolute_path.display(), e), None, false),
                 }
             }
             ToolCall::ListDir { path } => {
                 let absolute_path = self.working_dir.join(&path);
                 match self.command_processor.list_directory_smart(&absolute_path) {
                     Ok(items) => {
-                        if items.is_empty() { (true, "Directory is empty".to_string()) } else { (true, items.join("\n")) }
+                        if items.is_empty() { (true, "Directory is empty".to_string(), None, false) } else { (true, items.join("\n"), None, false) }
                     }
-                    Err(e) => (false, format!("Failed to list directory '{}': {}", absolute_path.display(), e)),
+                    Err(e) => (false, format!("Failed to list directory '{}': {}", absolute_path.display(), e), None, false),
                 }
             }
             ToolCall::WriteMemory { memory_type, content } => match self.write_memory(&memory_type, &content) {
-                Ok(()) => (true, format!("Successfully wrote to {} memory", memory_type)),
-                Err(e) => (false, format!("Failed to write to {} memory: {}", memory_type, e)),
+                Ok(()) => (true, format!("Successfully wrote to {} memory", memory_type), None, false),
+                Err(e) => (false, format!("Failed to write to {} memory: {}", memory_type, e), None, false),
             },
             ToolCall::ClearMemory { memory_type } => match self.clear_memory(&memory_type) {
-                Ok(()) => (true, format!("Successfully cleared {} memory", memory_type)),
-                Err(e) => (false, format!("Failed to clear {} memory: {}", memory_type, e)),
+                Ok(()) => (true, format!("Successfully cleared {} memory", memory_type), None, false),
+                Err(e) => (false, format!("Failed to clear {} memory: {}", memory_type, e), None, false),
             },
-            ToolCall::ScriptTool { name, args } => {
-                let ext = if cfg!(target_os = "windows") { "ps1" } else { "sh" };
-                let script_path = self.working_dir.join("prime").join(format!("tool_{}.{}", name, ext));
-                if !script_path.exists() {
-                    (false, format!("Script not found: {}", script_path.display()))
-                } else {
-                    let mut cmd = format!("{}", script_path.display());
-                    if !args.is_empty() {
-                        cmd.push_str(&format!(" {}", args.join(" ")));
+            ToolCall::ScriptTool { name, args, timeout } => {
+                if self.live_plugin_manager.plugins.iter().any(|p| p.name == name) {
+                    match self.live_plugin_manager.invoke(&name, &args, &self.working_dir) {
+                        Ok(out) => (true, out, None, false),
+                        Err(e) => (false, format!("Plugin tool '{}' failed: {}", name, e), None, false),
                     }
-                    match self.command_processor.execute_command(&cmd, Some(&self.working_dir)) {
-                        Ok((0, out)) => (true, out),
-                        Ok((code, out)) => (false, format!("Script failed with exit code {}\nOutput:\n{}", code, out)),
-                        Err(e) => (false, format!("Failed to execute script: {}", e)),
+                } else if self.plugin_manager.tools.iter().any(|t| t.name == name) {
+                    match self.plugin_manager.invoke(&name, &args) {
+                        Ok(out) => (true, out, None, false),
+                        Err(e) => (false, format!("Plugin tool '{}' failed: {}", name, e), None, false),
+                    }
+                } else {
+                    let ext = if cfg!(target_os = "windows") { "ps1" } else { "sh" };
+                    let script_path = self.working_dir.join("prime").join(format!("tool_{}.{}", name, ext));
+                    if !script_path.exists() {
+                        (false, format!("Script not found: {}", script_path.display()), None, false)
+                    } else if let Some(validation_error) = self.validate_script_args(&name, &args) {
+                        (false, validation_error, None, false)
+                    } else {
+                        let mut cmd = format!("{}", script_path.display());
+                        if !args.is_empty() {
+                            cmd.push_str(&format!(" {}", args.join(" ")));
+                        }
+                        let result = match resolve_timeout(timeout) {
+                            Some(timeout) => self.command_processor.execute_command_with_timeout(&cmd, Some(&self.working_dir), timeout, elevate),
+                            None if elevate => self.command_processor.execute_command_elevated(&cmd, Some(&self.working_dir)),
+                            None => self.command_processor.execute_command(&cmd, Some(&self.working_dir)),
+                        };
+                        classify_process_result("Script", result)
                     }
                 }
             }
-            ToolCall::CreateTool { name, desc, args, script_content } => {
+            ToolCall::CreateTool { name, desc, args, script_content, .. } => {
                 let ext = if cfg!(target_os = "windows") { "ps1" } else { "sh" };
                 let tool_path = self.working_dir.join("prime").join(format!("tool_{}.{}", name, ext));
                 let arg_parts: Vec<&str> = args.split_whitespace().collect();
@@ -663,9 +1361,67 @@ Now, begin.
                             }
                         }
                         self.reload_tools().ok();
-                        (true, format!("Created and loaded new tool: {} at {}", name, tool_path.display()))
+                        (true, format!("Created and loaded new tool: {} at {}", name, tool_path.display()), None, false)
+                    }
+                    Err(e) => (false, format!("Failed to create tool '{}': {}", tool_path.display(), e), None, false),
+                }
+            }
+            ToolCall::RunScript { lang, args, timeout, script_content } => {
+                let timeout = timeout.map(Duration::from_secs);
+                let result = self.command_processor.run_script(&lang, args.as_deref(), timeout, &script_content, Some(&self.working_dir));
+                classify_process_result("Script", result)
+            }
+            ToolCall::Help { filter } => (true, self.help_listing(filter.as_deref()), None, false),
+            ToolCall::Watch { paths, debounce_ms } => {
+                let absolute_paths: Vec<PathBuf> = paths.iter().map(|p| self.working_dir.join(p)).collect();
+                if let Some(missing) = absolute_paths.iter().find(|p| !p.exists()) {
+                    (false, format!("Path not found: {}", missing.display()), None, false)
+                } else {
+                    let debounce_ms = debounce_ms.unwrap_or(watch::DEFAULT_TOOL_WATCH_DEBOUNCE_MS);
+                    match watch::ActiveWatch::register(absolute_paths.clone(), debounce_ms) {
+                        Ok(active_watch) => {
+                            self.active_watches.push(active_watch);
+                            let listed = absolute_paths.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ");
+                            (true, format!("Watching for changes under: {} (debounce_ms={})", listed, debounce_ms), None, false)
+                        }
+                        Err(e) => (false, format!("Failed to register watch: {}", e), None, false),
                     }
-                    Err(e) => (false, format!("Failed to create tool '{}': {}", tool_path.display(), e)),
+                }
+            }
+            ToolCall::SetAlias { name, expansion } => match self.aliases.set(&self.working_dir, name.clone(), expansion.clone()) {
+                Ok(()) => (true, format!("Alias set: {} = {}", name, expansion), None, false),
+                Err(e) => (false, format!("Failed to save alias '{}': {}", name, e), None, false),
+            },
+            ToolCall::ClearAlias { name } => match self.aliases.clear(&self.working_dir, &name) {
+                Ok(true) => (true, format!("Alias cleared: {}", name), None, false),
+                Ok(false) => (false, format!("No such alias: {}", name), None, false),
+                Err(e) => (false, format!("Failed to clear alias '{}': {}", name, e), None, false),
+            },
+            ToolCall::Archive { paths, dest, format } => {
+                let absolute_dest = self.working_dir.join(&dest);
+                if let Err(e) = self.command_processor.write_file_to_path(&absolute_dest, "", false) {
+                    (false, format!("Failed to prepare archive destination '{}': {}", absolute_dest.display(), e), None, false)
+                } else {
+                    let flag = if format == "xz" { "J" } else { "z" };
+                    let quoted_paths = paths.iter().map(|p| commands::shell_quote(p)).collect::<Vec<_>>().join(" ");
+                    let cmd = format!("tar -c{}f {} {}", flag, commands::shell_quote(&absolute_dest.display().to_string()), quoted_paths);
+                    let result = self.command_processor.execute_command(&cmd, Some(&self.working_dir));
+                    classify_process_result("Archive", result)
+                }
+            }
+            ToolCall::Extract { archive, dest } => {
+                let absolute_archive = self.working_dir.join(&archive);
+                let absolute_dest = self.working_dir.join(&dest);
+                if let Err(e) = fs::create_dir_all(&absolute_dest).with_context(|| format!("Failed to create directory: {}", absolute_dest.display())) {
+                    (false, format!("{}", e), None, false)
+                } else {
+                    let cmd = format!(
+                        "tar -xf {} -C {}",
+                        commands::shell_quote(&absolute_archive.display().to_string()),
+                        commands::shell_quote(&absolute_dest.display().to_string())
+                    );
+                    let result = self.command_processor.execute_command(&cmd, Some(&self.working_dir));
+                    classify_process_result("Extract", result)
                 }
             }
         };
@@ -674,24 +1430,37 @@ Now, begin.
                 println!("{}", format!("│ {}", line).dim());
             }
         }
-        ToolExecutionResult { tool_call_str, success, output }
+        ToolExecutionResult { tool_call_str, success, output, exit_code, terminated_by_signal }
     }
 
     pub fn format_tool_results_for_llm(&self, results: &[ToolExecutionResult]) -> Result<String> {
         let formatted_results = results.iter().enumerate().map(|(idx, result)| {
             let status = if result.success { "SUCCESS" } else { "FAILURE" };
-            format!("<tool_output id=\"{}\" for=\"{}\" status=\"{}\">\n{}\n</tool_output>", idx, result.tool_call_str, status, result.output.trim())
+            let exit_attr = result.exit_classification().map(|c| format!(" exit=\"{}\"", c)).unwrap_or_default();
+            format!(
+                "<tool_output id=\"{}\" for=\"{}\" status=\"{}\"{}>\n{}\n</tool_output>",
+                idx, result.tool_call_str, status, exit_attr, result.output.trim()
+            )
         }).collect::<Vec<String>>().join("\n");
         Ok(formatted_results)
     }
 
     pub fn format_tool_failure_for_llm(&self, result: &ToolExecutionResult) -> Result<String> {
-        let formatted_result = format!("<tool_output for=\"{}\" status=\"FAILURE\">\n{}\n</tool_output>", result.tool_call_str, result.output.trim());
+        let exit_attr = result.exit_classification().map(|c| format!(" exit=\"{}\"", c)).unwrap_or_default();
+        let hint = match result.exit_code {
+            Some(127) => "\nHint: the program was not found on PATH. Use create_tool to implement it directly, or install the missing dependency first.",
+            _ if result.terminated_by_signal => "\nHint: the process was killed by a signal (it may have hung or exceeded a timeout); consider a smaller scope or a longer timeout.",
+            _ => "",
+        };
+        let formatted_result = format!(
+            "<tool_output for=\"{}\" status=\"FAILURE\"{}>\n{}{}\n</tool_output>",
+            result.tool_call_str, exit_attr, result.output.trim(), hint
+        );
         Ok(formatted_result)
     }
 
     pub fn get_history(&self, limit: Option<usize>) -> Result<Vec<ChatMessage>> {
-        let log_content = fs::read_to_string(&self.session_log_path).unwrap_or_default();
+        let log_content = self.read_log_content()?;
         let mut messages = Vec::new();
         for section in log_content.split("\n## ").filter(|s| !s.trim().is_empty()) {
             if let Some((header, content_part)) = section.split_once('\n') {
@@ -699,7 +1468,7 @@ Now, begin.
                     Some(ChatRole::User)
                 } else if header.starts_with("Prime Response") {
                     Some(ChatRole::Assistant)
-                } else if header.starts_with("Tool Results") || header.starts_with("Tool Failure") || header.starts_with("System") {
+                } else if header.starts_with("Tool Results") || header.starts_with("Tool Failure") || header.starts_with("System") || header.starts_with("Watch Event") {
                     Some(ChatRole::User)
                 } else {
                     None
@@ -722,7 +1491,7 @@ Now, begin.
     }
 
     pub fn list_messages(&self) -> Result<String> {
-        fs::read_to_string(&self.session_log_path).context("Could not read session log file.")
+        self.read_log_content()
     }
 
     pub fn read_memory(&self, memory_type: Option<&str>) -> Result<String> {
@@ -737,6 +1506,86 @@ Now, begin.
         self.memory_manager.clear_memory(memory_type)
     }
 
+    /// Flattens every builtin, `DiscoveredTool`, plugin, live plugin, and
+    /// alias into one `ToolDescription` each, the shared backing data for
+    /// `complete`/`describe_tool`. Mirrors `help_listing`'s registry walk but
+    /// keeps the argument *names* (not a rendered signature string), since
+    /// that's what a completer needs to offer after the command word.
+    fn tool_catalog(&self) -> Vec<ToolDescription> {
+        let mut catalog: Vec<ToolDescription> = BUILTIN_TOOLS
+            .iter()
+            .map(|(name, desc, _sig, arg_names)| ToolDescription {
+                name: name.to_string(),
+                kind: "builtin",
+                desc: desc.to_string(),
+                arg_names: arg_names.iter().map(|a| a.to_string()).collect(),
+            })
+            .collect();
+        for tool in &self.discovered_tools {
+            catalog.push(ToolDescription {
+                name: tool.name.clone(),
+                kind: "custom",
+                desc: tool.desc.clone(),
+                arg_names: tool.arg_spec.iter().map(|spec| spec.name.clone()).collect(),
+            });
+        }
+        for tool in &self.plugin_manager.tools {
+            catalog.push(ToolDescription {
+                name: tool.name.clone(),
+                kind: "plugin",
+                desc: tool.desc.clone(),
+                arg_names: tool.args.split_whitespace().map(|a| a.to_string()).collect(),
+            });
+        }
+        for plugin in &self.live_plugin_manager.plugins {
+            catalog.push(ToolDescription {
+                name: plugin.name.clone(),
+                kind: "live plugin",
+                desc: plugin.desc.clone(),
+                arg_names: plugin.args.split_whitespace().map(|a| a.to_string()).collect(),
+            });
+        }
+        for (name, expansion) in self.aliases.iter() {
+            catalog.push(ToolDescription {
+                name: name.to_string(),
+                kind: "alias",
+                desc: format!("expands to: {}", expansion),
+                arg_names: Vec::new(),
+            });
+        }
+        catalog
+    }
+
+    /// Looks up one entry of `tool_catalog` by exact name, for a front-end
+    /// that already knows which tool it's asking about (e.g. to render an
+    /// inline signature hint once the user has typed a full command word).
+    pub fn describe_tool(&self, name: &str) -> Option<ToolDescription> {
+        self.tool_catalog().into_iter().find(|tool| tool.name == name)
+    }
+
+    /// Tab-completion over the tool catalog, mirroring how a shell completer
+    /// enumerates `/bin` plus a static command table: with no space yet in
+    /// `prefix`, returns matching tool/alias names for the command word;
+    /// once a command word and a space are present, returns that tool's
+    /// argument names matching whatever's been typed of the next word.
+    /// Returns no candidates for an unrecognized command word.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let catalog = self.tool_catalog();
+        let mut candidates: Vec<String> = match prefix.split_once(' ') {
+            None => catalog.into_iter().map(|tool| tool.name).filter(|name| name.starts_with(prefix)).collect(),
+            Some((command, rest)) => {
+                let Some(tool) = catalog.into_iter().find(|tool| tool.name == command) else {
+                    return Vec::new();
+                };
+                let arg_prefix = rest.rsplit(' ').next().unwrap_or("");
+                tool.arg_names.into_iter().filter(|name| name.starts_with(arg_prefix)).collect()
+            }
+        };
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+
     pub fn list_tools(&self) -> String {
         let mut out = "Built-in Tools:\n".to_string();
         out.push_str("- shell: Execute any shell command\n");
@@ -747,15 +1596,90 @@ Now, begin.
         out.push_str("- write_memory: Add to long/short-term memory\n");
         out.push_str("- clear_memory: Clear memory type\n");
         out.push_str("- create_tool: Create a new self-extending tool script\n");
+        out.push_str("- archive: Package files/directories into a .tar.xz or .tar.gz archive\n");
+        out.push_str("- extract: Extract an archive into a destination directory\n");
         out.push_str("\nDiscovered Custom Tools (./prime/):\n");
         if self.discovered_tools.is_empty() {
             out.push_str("None found. Use create_tool to build your own!\n");
         } else {
             for tool in &self.discovered_tools {
+                out.push_str(&format!(
+                    "- {}: {} (args: {}, path: {})\n",
+                    tool.name,
+                    tool.desc,
+                    parser::render_tool_args(&tool.arg_spec),
+                    tool.path.display()
+                ));
+            }
+        }
+        out.push_str("\nPlugin Tools (~/.prime/plugins/):\n");
+        if self.plugin_manager.tools.is_empty() {
+            out.push_str("None found. Drop an executable in ~/.prime/plugins to register one.\n");
+        } else {
+            for tool in &self.plugin_manager.tools {
                 out.push_str(&format!("- {}: {} (args: {}, path: {})\n", tool.name, tool.desc, tool.args, tool.path.display()));
             }
         }
+        out.push_str("\nLive Plugin Tools (./prime/plugin_*):\n");
+        if self.live_plugin_manager.plugins.is_empty() {
+            out.push_str("None found. Drop a long-lived plugin executable named plugin_<name> in ./prime to register one.\n");
+        } else {
+            for plugin in &self.live_plugin_manager.plugins {
+                out.push_str(&format!("- {}: {} (args: {}, path: {})\n", plugin.name, plugin.desc, plugin.args, plugin.path.display()));
+            }
+        }
+        out.push_str("\nAliases (./prime/aliases.txt):\n");
+        if self.aliases.is_empty() {
+            out.push_str("None set. Use alias: <name> = <expansion> to create one.\n");
+        } else {
+            for (name, expansion) in self.aliases.iter() {
+                out.push_str(&format!("- {} = {}\n", name, expansion));
+            }
+        }
+        out
+    }
+
+    /// Builds the `ToolCall::Help` listing: every built-in tool plus each
+    /// `DiscoveredTool`/plugin, flattened into one `name (kind): desc — signature`
+    /// entry per line and optionally filtered to entries whose name or
+    /// description contains `filter` (case-insensitive). A mid-session, cheap
+    /// alternative to re-reading the whole `tools_section` of the system prompt,
+    /// useful right after a `create_tool` + `reload_tools` to confirm a new tool
+    /// registered, or to audit why a malformed `## TOOL:` header didn't.
+    fn help_listing(&self, filter: Option<&str>) -> String {
+        let mut entries: Vec<(String, &'static str, String, String)> = BUILTIN_TOOLS
+            .iter()
+            .map(|(name, desc, sig, _arg_names)| (name.to_string(), "builtin", desc.to_string(), sig.to_string()))
+            .collect();
+        for tool in &self.discovered_tools {
+            entries.push((tool.name.clone(), "custom", tool.desc.clone(), parser::render_tool_args(&tool.arg_spec)));
+        }
+        for tool in &self.plugin_manager.tools {
+            entries.push((tool.name.clone(), "plugin", tool.desc.clone(), tool.args.clone()));
+        }
+        for plugin in &self.live_plugin_manager.plugins {
+            entries.push((plugin.name.clone(), "live plugin", plugin.desc.clone(), plugin.args.clone()));
+        }
+        for (name, expansion) in self.aliases.iter() {
+            entries.push((name.to_string(), "alias", format!("expands to: {}", expansion), String::new()));
+        }
+
+        let needle = filter.map(|f| f.to_lowercase());
+        let mut out = String::new();
+        for (name, kind, desc, signature) in &entries {
+            if let Some(needle) = &needle {
+                if !name.to_lowercase().contains(needle.as_str()) && !desc.to_lowercase().contains(needle.as_str()) {
+                    continue;
+                }
+            }
+            out.push_str(&format!("- {} ({}): {} — {}\n", name, kind, desc, signature));
+        }
+        if out.is_empty() {
+            out = match filter {
+                Some(f) => format!("No tools matched filter '{}'.\n", f),
+                None => "No tools registered.\n".to_string(),
+            };
+        }
         out
     }
 }
- 
\ No newline at end of file
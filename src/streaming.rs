@@ -14,11 +14,40 @@ pub enum StreamToken {
     Done,
 }
 
+/// Where `StreamHandler` is within a ` ``` ` fence, tracked a character at a
+/// time so an opener or closer can be recognized even when it's split across
+/// many `process_token` calls.
+enum FenceState {
+    /// Not looking at anything fence-related right now.
+    None,
+    /// Seen `n` (1 or 2) consecutive backticks that could still turn into an
+    /// opening fence, or could just be stray backticks in plain text.
+    MaybeFence(u8),
+    /// Saw the opening ` ``` `, now collecting the language word up to the
+    /// newline that ends the fence line.
+    InLang(String),
+    /// Inside the body of a fenced block whose language is the given string.
+    /// `"primeactions"` bodies are hidden from output until the closing
+    /// fence turns them into a `ToolCall`; every other language's opener line
+    /// was already flushed as text, and its body flushes as text at the
+    /// closing fence too, so the close is recognized unambiguously instead
+    /// of being re-parsed as a fresh opener.
+    InBlock(String),
+}
+
 /// Streaming response handler with intelligent buffering
 pub struct StreamHandler {
-    buffer: String,
-    in_code_block: bool,
-    code_block_lang: Option<String>,
+    state: FenceState,
+    /// Characters tentatively consumed by fence detection that haven't been
+    /// committed anywhere yet: backticks awaiting disambiguation in
+    /// `MaybeFence`/`InLang`, or a trailing run of backticks that might be a
+    /// closing fence while `InBlock`.
+    pending: String,
+    /// Confirmed plain text, flushed to a `StreamToken::Text` on a timer.
+    text_buffer: String,
+    /// Body of the current `primeactions` block, hidden until the closing
+    /// fence turns it into a single `StreamToken::ToolCall`.
+    block_body: String,
     last_flush: Instant,
     flush_interval: Duration,
 }
@@ -26,9 +55,10 @@ pub struct StreamHandler {
 impl StreamHandler {
     pub fn new() -> Self {
         Self {
-            buffer: String::new(),
-            in_code_block: false,
-            code_block_lang: None,
+            state: FenceState::None,
+            pending: String::new(),
+            text_buffer: String::new(),
+            block_body: String::new(),
             last_flush: Instant::now(),
             flush_interval: Duration::from_millis(50), // Smooth 20 FPS display
         }
@@ -37,72 +67,131 @@ impl StreamHandler {
     /// Process incoming token and determine if it should be displayed or buffered
     pub fn process_token(&mut self, token: &str) -> Vec<StreamToken> {
         let mut output = Vec::new();
-        self.buffer.push_str(token);
-
-        // Check for code block markers
-        if self.buffer.contains("```") {
-            if let Some(idx) = self.buffer.rfind("```") {
-                let before = &self.buffer[..idx];
-                let after = &self.buffer[idx..];
-                
-                if !self.in_code_block {
-                    // Starting code block
-                    if let Some(newline_idx) = after.find('\n') {
-                        let lang = after[3..newline_idx].trim().to_string();
-                        self.code_block_lang = Some(lang.clone());
-                        self.in_code_block = true;
-                        
-                        // Check if this is a primeactions block
+
+        for ch in token.chars() {
+            self.consume_char(ch, &mut output);
+        }
+
+        // Outside a primeactions block, confirmed text flushes on its own
+        // schedule regardless of any fence still being disambiguated, so a
+        // lone backtick (or an opener that never completes) never holds up
+        // everything that arrived before it.
+        if !matches!(self.state, FenceState::InBlock(_))
+            && self.last_flush.elapsed() >= self.flush_interval
+            && !self.text_buffer.is_empty()
+        {
+            output.push(StreamToken::Text(std::mem::take(&mut self.text_buffer)));
+            self.last_flush = Instant::now();
+        }
+
+        output
+    }
+
+    fn consume_char(&mut self, ch: char, output: &mut Vec<StreamToken>) {
+        match &mut self.state {
+            FenceState::None => {
+                if ch == '`' {
+                    self.pending.push(ch);
+                    self.state = FenceState::MaybeFence(1);
+                } else {
+                    self.text_buffer.push(ch);
+                }
+            }
+            FenceState::MaybeFence(n) => {
+                if ch == '`' {
+                    self.pending.push(ch);
+                    if *n == 2 {
+                        self.state = FenceState::InLang(String::new());
+                    } else {
+                        *n += 1;
+                    }
+                } else {
+                    // False alarm: the backticks were plain text after all.
+                    self.text_buffer.push_str(&self.pending);
+                    self.pending.clear();
+                    self.text_buffer.push(ch);
+                    self.state = FenceState::None;
+                }
+            }
+            FenceState::InLang(lang) => {
+                if ch == '\n' {
+                    if lang == "primeactions" {
+                        // Hide the opener entirely; only the block body is buffered.
+                        self.pending.clear();
+                        self.block_body.clear();
+                        self.state = FenceState::InBlock("primeactions".to_string());
+                    } else {
+                        // A regular fence: emit the opener line as ordinary text,
+                        // then track the body under `InBlock` too (instead of
+                        // falling back to `None`) so the closing fence is
+                        // recognized unambiguously rather than being re-parsed
+                        // as a fresh opener and parked in `pending`.
+                        self.pending.push(ch);
+                        self.text_buffer.push_str(&self.pending);
+                        self.pending.clear();
+                        let lang = lang.clone();
+                        self.block_body.clear();
+                        self.state = FenceState::InBlock(lang);
+                    }
+                } else {
+                    lang.push(ch);
+                    self.pending.push(ch);
+                }
+            }
+            FenceState::InBlock(lang) => {
+                if ch == '`' {
+                    self.pending.push(ch);
+                    if self.pending.len() == 3 {
+                        let lang = lang.clone();
+                        self.pending.clear();
+                        self.state = FenceState::None;
                         if lang == "primeactions" {
-                            // Buffer the entire block for tool parsing
-                            return output;
-                        }
-                        
-                        // Flush everything before the code block
-                        if !before.is_empty() {
-                            output.push(StreamToken::Text(before.to_string()));
+                            output.push(StreamToken::ToolCall(std::mem::take(&mut self.block_body)));
+                        } else {
+                            // Regular block: reassemble and flush the closing
+                            // fence synchronously, the same turn it completes,
+                            // rather than waiting on the periodic flush timer.
+                            self.text_buffer.push_str(&std::mem::take(&mut self.block_body));
+                            self.text_buffer.push_str("```");
+                            output.push(StreamToken::Text(std::mem::take(&mut self.text_buffer)));
                         }
-                        output.push(StreamToken::Text(after[..=newline_idx].to_string()));
-                        self.buffer = after[newline_idx + 1..].to_string();
                     }
                 } else {
-                    // Ending code block
-                    self.in_code_block = false;
-                    
-                    // If it was a primeactions block, emit as tool call
-                    if self.code_block_lang.as_deref() == Some("primeactions") {
-                        output.push(StreamToken::ToolCall(before.to_string()));
-                        self.buffer.clear();
-                        self.code_block_lang = None;
-                        return output;
+                    if !self.pending.is_empty() {
+                        // A partial run of backticks that didn't close the
+                        // block after all; it was part of the body.
+                        self.block_body.push_str(&self.pending);
+                        self.pending.clear();
                     }
-                    
-                    // Regular code block - flush it
-                    output.push(StreamToken::Text(self.buffer.clone()));
-                    self.buffer.clear();
-                    self.code_block_lang = None;
+                    self.block_body.push(ch);
                 }
             }
         }
-
-        // Flush buffer periodically for smooth display (but not during primeactions)
-        if !self.in_code_block || self.code_block_lang.as_deref() != Some("primeactions") {
-            if self.last_flush.elapsed() >= self.flush_interval && !self.buffer.is_empty() {
-                output.push(StreamToken::Text(self.buffer.clone()));
-                self.buffer.clear();
-                self.last_flush = Instant::now();
-            }
-        }
-
-        output
     }
 
     /// Flush any remaining buffered content
     pub fn flush(&mut self) -> Option<StreamToken> {
-        if !self.buffer.is_empty() {
-            let content = self.buffer.clone();
-            self.buffer.clear();
-            Some(StreamToken::Text(content))
+        // Anything still pending (an unresolved fence prefix, or a
+        // primeactions opener/body that never closed) is surfaced as plain
+        // text rather than silently dropped.
+        if let FenceState::InBlock(lang) = &self.state {
+            if lang == "primeactions" {
+                // The opener was hidden when the block started; reconstruct
+                // it since a regular block's opener, by contrast, was
+                // already flushed into `text_buffer` as ordinary text.
+                self.text_buffer.push_str("```");
+                self.text_buffer.push_str(lang);
+                self.text_buffer.push('\n');
+            }
+            self.text_buffer.push_str(&self.block_body);
+        }
+        self.text_buffer.push_str(&self.pending);
+        self.pending.clear();
+        self.block_body.clear();
+        self.state = FenceState::None;
+
+        if !self.text_buffer.is_empty() {
+            Some(StreamToken::Text(std::mem::take(&mut self.text_buffer)))
         } else {
             None
         }
@@ -122,14 +211,14 @@ mod tests {
     #[test]
     fn test_regular_text_streaming() {
         let mut handler = StreamHandler::new();
-        
+
         let tokens = handler.process_token("Hello ");
         assert!(tokens.is_empty()); // Buffered
-        
+
         std::thread::sleep(Duration::from_millis(60));
         let tokens = handler.process_token("world");
         assert_eq!(tokens.len(), 1);
-        
+
         if let StreamToken::Text(text) = &tokens[0] {
             assert_eq!(text, "Hello ");
         }
@@ -138,11 +227,11 @@ mod tests {
     #[test]
     fn test_primeactions_buffering() {
         let mut handler = StreamHandler::new();
-        
+
         handler.process_token("```primeactions\n");
         handler.process_token("shell: ls\n");
         let tokens = handler.process_token("```");
-        
+
         assert_eq!(tokens.len(), 1);
         if let StreamToken::ToolCall(content) = &tokens[0] {
             assert!(content.contains("shell: ls"));
@@ -152,12 +241,68 @@ mod tests {
     #[test]
     fn test_regular_code_block() {
         let mut handler = StreamHandler::new();
-        
+
         handler.process_token("```python\n");
         handler.process_token("print('hello')\n");
         let tokens = handler.process_token("```");
-        
+
         // Regular code blocks are flushed as text
         assert!(tokens.iter().any(|t| matches!(t, StreamToken::Text(_))));
     }
+
+    #[test]
+    fn test_primeactions_opener_split_across_many_tokens() {
+        let mut handler = StreamHandler::new();
+
+        // The fence, language tag, and newline all arrive as separate
+        // fragments, including one that splits "prime" from "actions".
+        handler.process_token("``");
+        handler.process_token("`");
+        handler.process_token("prime");
+        handler.process_token("actions");
+        handler.process_token("\n");
+        handler.process_token("shell: echo hi\n");
+        let tokens = handler.process_token("```");
+
+        assert_eq!(tokens.len(), 1);
+        match &tokens[0] {
+            StreamToken::ToolCall(content) => assert!(content.contains("shell: echo hi")),
+            other => panic!("expected a ToolCall token, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_lone_backtick_in_plain_text_is_not_held_hostage() {
+        let mut handler = StreamHandler::new();
+
+        // A single backtick followed by ordinary prose: never becomes a
+        // fence, so it should flush out as text like anything else once the
+        // flush interval elapses.
+        handler.process_token("Use `cargo test` to run the suite");
+        std::thread::sleep(Duration::from_millis(60));
+        let tokens = handler.process_token(" please.");
+
+        assert_eq!(tokens.len(), 1);
+        if let StreamToken::Text(text) = &tokens[0] {
+            assert!(text.contains("`cargo test`"));
+        } else {
+            panic!("expected a Text token");
+        }
+    }
+
+    #[test]
+    fn test_unclosed_primeactions_block_is_flushed_on_stream_end() {
+        let mut handler = StreamHandler::new();
+
+        let tokens = handler.process_token("```primeactions\nshell: ls\n");
+        assert!(tokens.is_empty()); // Hidden while the block is open
+
+        // The stream ends without a closing fence; flush() must not drop
+        // the buffered body silently.
+        let flushed = handler.flush();
+        match flushed {
+            Some(StreamToken::Text(text)) => assert!(text.contains("shell: ls")),
+            other => panic!("expected leftover content to flush as text, got {:?}", other),
+        }
+    }
 }
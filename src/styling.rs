@@ -1,10 +1,27 @@
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use crossterm::style::{Color, Stylize};
 use std::borrow::Cow;
 
-pub static STYLER: Lazy<Styler> = Lazy::new(|| Styler::new());
+use crate::config::ThemeConfig;
 
-pub struct Styler {
+/// The resolved `[theme]` config, set once by `init_theme` during startup and
+/// read the first time `STYLER` is dereferenced. Reading `STYLER` before
+/// `init_theme` runs just falls back to the auto-detected default preset.
+static RESOLVED_THEME: OnceCell<ThemeConfig> = OnceCell::new();
+
+/// Records the startup-resolved theme so `STYLER` picks it up on first use.
+/// Call this once from `init_session`, before any styling happens.
+pub fn init_theme(theme: ThemeConfig) {
+    let _ = RESOLVED_THEME.set(theme);
+}
+
+pub static STYLER: Lazy<Styler> = Lazy::new(|| {
+    let theme = RESOLVED_THEME.get().cloned().unwrap_or_default();
+    Styler::from_config(&theme)
+});
+
+/// One named color per role a `Styler` can apply.
+struct RoleColors {
     success: Color,
     error: Color,
     warning: Color,
@@ -19,9 +36,38 @@ pub struct Styler {
     llm_response: Color,
 }
 
-impl Styler {
-    fn new() -> Self {
-        Self {
+/// Built-in palettes, selectable by name via `[theme] preset`.
+fn preset_colors(name: &str) -> RoleColors {
+    match name {
+        "light" => RoleColors {
+            success: Color::DarkGreen,
+            error: Color::DarkRed,
+            warning: Color::DarkYellow,
+            info: Color::DarkBlue,
+            bold_white: Color::Black,
+            dim_gray: Color::Grey,
+            command_exec: Color::DarkCyan,
+            prompt: Color::DarkMagenta,
+            separator: Color::Grey,
+            header: Color::DarkBlue,
+            command_alt: Color::DarkCyan,
+            llm_response: Color::DarkGreen,
+        },
+        "mono" => RoleColors {
+            success: Color::White,
+            error: Color::White,
+            warning: Color::White,
+            info: Color::White,
+            bold_white: Color::White,
+            dim_gray: Color::DarkGrey,
+            command_exec: Color::White,
+            prompt: Color::White,
+            separator: Color::DarkGrey,
+            header: Color::White,
+            command_alt: Color::White,
+            llm_response: Color::White,
+        },
+        _ => RoleColors {
             success: Color::Green,
             error: Color::Red,
             warning: Color::Yellow,
@@ -34,6 +80,89 @@ impl Styler {
             header: Color::Blue,
             command_alt: Color::Cyan,
             llm_response: Color::Green,
+        },
+    }
+}
+
+/// Parses a role color from config: a handful of named ANSI colors, or a
+/// `#rrggbb` hex triplet rendered as 24-bit color. Unknown names fall back to
+/// the preset's color for that role.
+fn parse_color(value: &str) -> Option<Color> {
+    match value.trim().to_lowercase().as_str() {
+        "black" => Some(Color::Black),
+        "red" => Some(Color::Red),
+        "green" => Some(Color::Green),
+        "yellow" => Some(Color::Yellow),
+        "blue" => Some(Color::Blue),
+        "magenta" => Some(Color::Magenta),
+        "cyan" => Some(Color::Cyan),
+        "white" => Some(Color::White),
+        "grey" | "gray" | "dark_grey" | "dark_gray" => Some(Color::DarkGrey),
+        hex if hex.starts_with('#') && hex.len() == 7 => {
+            let r = u8::from_str_radix(&hex[1..3], 16).ok()?;
+            let g = u8::from_str_radix(&hex[3..5], 16).ok()?;
+            let b = u8::from_str_radix(&hex[5..7], 16).ok()?;
+            Some(Color::Rgb { r, g, b })
+        }
+        _ => None,
+    }
+}
+
+/// Reads the `COLORFGBG` environment variable (set by most terminal emulators)
+/// to guess whether the background is light or dark, defaulting to "dark" when
+/// the variable is absent or unparseable. Mirrors the light/dark auto-pick
+/// convention used by terminal-aware tools like `bat`.
+fn detect_preset_name() -> String {
+    if let Ok(colorfgbg) = std::env::var("COLORFGBG") {
+        if let Some(bg) = colorfgbg.split(';').last() {
+            if let Ok(code) = bg.parse::<u8>() {
+                // In the basic 16-color xterm palette, 7 and 15 are the light
+                // greys/white commonly used as a light terminal's background.
+                if code == 7 || code == 15 {
+                    return "light".to_string();
+                }
+            }
+        }
+    }
+    "dark".to_string()
+}
+
+pub struct Styler {
+    success: Color,
+    error: Color,
+    warning: Color,
+    info: Color,
+    bold_white: Color,
+    dim_gray: Color,
+    command_exec: Color,
+    prompt: Color,
+    separator: Color,
+    header: Color,
+    command_alt: Color,
+    llm_response: Color,
+}
+
+impl Styler {
+    /// Builds a `Styler` from a `[theme]` config: resolves `preset` (or
+    /// auto-detects light/dark when unset) into a base palette, then applies
+    /// any per-role color overrides on top.
+    fn from_config(theme: &ThemeConfig) -> Self {
+        let preset_name = theme.preset.clone().unwrap_or_else(detect_preset_name);
+        let palette = preset_colors(&preset_name);
+
+        Self {
+            success: theme.success.as_deref().and_then(parse_color).unwrap_or(palette.success),
+            error: theme.error.as_deref().and_then(parse_color).unwrap_or(palette.error),
+            warning: theme.warning.as_deref().and_then(parse_color).unwrap_or(palette.warning),
+            info: theme.info.as_deref().and_then(parse_color).unwrap_or(palette.info),
+            bold_white: palette.bold_white,
+            dim_gray: palette.dim_gray,
+            command_exec: theme.command_exec.as_deref().and_then(parse_color).unwrap_or(palette.command_exec),
+            prompt: theme.prompt.as_deref().and_then(parse_color).unwrap_or(palette.prompt),
+            separator: theme.separator.as_deref().and_then(parse_color).unwrap_or(palette.separator),
+            header: theme.header.as_deref().and_then(parse_color).unwrap_or(palette.header),
+            command_alt: palette.command_alt,
+            llm_response: theme.llm_response.as_deref().and_then(parse_color).unwrap_or(palette.llm_response),
         }
     }
 
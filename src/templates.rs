@@ -1,10 +1,67 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use handlebars::Handlebars;
+use serde::Deserialize;
 use serde_json::Value;
-use anyhow::{Result, anyhow};
+use anyhow::{Context, Result, anyhow};
+
+fn default_required() -> bool {
+    true
+}
+
+/// One variable a template's sidecar `<name>.json` schema declares: its
+/// expected JSON type (`"string"`, `"number"`, `"boolean"`, `"array"`, or
+/// `"object"`) and whether `render_template` should reject a call that
+/// omits it. Required unless the schema says otherwise.
+#[derive(Debug, Clone, Deserialize)]
+struct TemplateParam {
+    #[serde(rename = "type")]
+    ty: String,
+    #[serde(default = "default_required")]
+    required: bool,
+}
+
+type TemplateSchema = HashMap<String, TemplateParam>;
+
+/// Checks `value`'s runtime JSON type against a schema's declared `ty`.
+/// Unknown type names are treated as "anything goes" rather than a hard
+/// error, so a typo in a schema doesn't block every render.
+fn value_matches_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+/// Validates `data` against `schema`, erroring on a missing required
+/// variable or a wrong-typed one, so a misspelled `{{package}}` fails loudly
+/// instead of silently producing `pip install `.
+fn validate_against_schema(schema: &TemplateSchema, data: &Value) -> Result<()> {
+    let obj = data.as_object().ok_or_else(|| anyhow!("Template data must be a JSON object"))?;
+    for (name, param) in schema {
+        match obj.get(name) {
+            Some(value) => {
+                if !value_matches_type(value, &param.ty) {
+                    return Err(anyhow!("Variable '{}' expects type '{}', got {}", name, param.ty, value));
+                }
+            }
+            None if param.required => {
+                return Err(anyhow!("Missing required template variable '{}'", name));
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
 
 pub struct TaskTemplates {
     templates: HashMap<String, String>,
+    schemas: HashMap<String, TemplateSchema>,
     handlebars: Handlebars<'static>,
 }
 
@@ -63,21 +120,72 @@ Install core dependencies:
                 .expect("Failed to register template");
         }
         
-        Self { templates, handlebars }
+        Self { templates, schemas: HashMap::new(), handlebars }
     }
-    
+
+    /// Builds on `new()`'s three built-in templates by also loading every
+    /// `*.hbs` file directly under `dir` as a named template (the file stem
+    /// is the name), registering any `*.hbs` files under `dir/partials/` as
+    /// Handlebars partials (so templates can `{{> header}}` shared
+    /// fragments), and pairing each template with its optional sidecar
+    /// `<name>.json` parameter schema. Returns just the built-ins,
+    /// unmodified, if `dir` doesn't exist.
+    pub fn load_from_dir(dir: &Path) -> Result<Self> {
+        let mut task_templates = Self::new();
+        if !dir.is_dir() {
+            return Ok(task_templates);
+        }
+
+        let partials_dir = dir.join("partials");
+        if partials_dir.is_dir() {
+            for entry in fs::read_dir(&partials_dir).with_context(|| format!("Failed to read partials dir: {}", partials_dir.display()))? {
+                let path = entry.with_context(|| format!("Error reading entry in {}", partials_dir.display()))?.path();
+                if path.extension().map_or(false, |ext| ext == "hbs") {
+                    let name = path.file_stem().unwrap().to_string_lossy().to_string();
+                    let content = fs::read_to_string(&path).with_context(|| format!("Failed to read partial: {}", path.display()))?;
+                    task_templates.handlebars.register_partial(&name, &content)
+                        .with_context(|| format!("Failed to register partial '{}'", name))?;
+                }
+            }
+        }
+
+        for entry in fs::read_dir(dir).with_context(|| format!("Failed to read templates dir: {}", dir.display()))? {
+            let path = entry.with_context(|| format!("Error reading entry in {}", dir.display()))?.path();
+            if path.is_dir() || path.extension().map_or(true, |ext| ext != "hbs") {
+                continue;
+            }
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let content = fs::read_to_string(&path).with_context(|| format!("Failed to read template: {}", path.display()))?;
+            task_templates.handlebars.register_template_string(&name, &content)
+                .with_context(|| format!("Failed to register template '{}'", name))?;
+            task_templates.templates.insert(name.clone(), content);
+
+            let schema_path = path.with_extension("json");
+            if schema_path.is_file() {
+                let schema_content = fs::read_to_string(&schema_path).with_context(|| format!("Failed to read schema: {}", schema_path.display()))?;
+                let schema: TemplateSchema = serde_json::from_str(&schema_content)
+                    .with_context(|| format!("Invalid schema in {}", schema_path.display()))?;
+                task_templates.schemas.insert(name, schema);
+            }
+        }
+
+        Ok(task_templates)
+    }
+
     pub fn render_template(&self, template_name: &str, data: &Value) -> Result<String> {
-        if let Some(_) = self.templates.get(template_name) {
-            Ok(self.handlebars.render(template_name, data)?)
-        } else {
-            Err(anyhow!("Template '{}' not found", template_name))
+        if self.templates.get(template_name).is_none() {
+            return Err(anyhow!("Template '{}' not found", template_name));
+        }
+        if let Some(schema) = self.schemas.get(template_name) {
+            validate_against_schema(schema, data)?;
         }
+        Ok(self.handlebars.render(template_name, data)?)
     }
-    
+
     pub fn list_templates(&self) -> Vec<String> {
         self.templates.keys().cloned().collect()
     }
-    
+
     pub fn add_template(&mut self, name: String, template: String) -> Result<()> {
         // Validate template first
         self.handlebars.register_template_string(&name, &template)?;
@@ -111,4 +219,29 @@ mod tests {
         assert!(result.contains("# test-project"));
         assert!(result.contains("A test project"));
     }
+
+    #[test]
+    fn test_load_from_dir_registers_templates_partials_and_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("partials")).unwrap();
+        fs::write(dir.path().join("partials").join("header.hbs"), "== {{title}} ==").unwrap();
+        fs::write(dir.path().join("greet.hbs"), "{{> header}}\nHello, {{name}}!").unwrap();
+        fs::write(dir.path().join("greet.json"), r#"{"name": {"type": "string"}, "title": {"type": "string", "required": false}}"#).unwrap();
+
+        let templates = TaskTemplates::load_from_dir(dir.path()).unwrap();
+        assert!(templates.list_templates().contains(&"greet".to_string()));
+
+        let result = templates.render_template("greet", &json!({"name": "Ada", "title": "Welcome"})).unwrap();
+        assert!(result.contains("== Welcome =="));
+        assert!(result.contains("Hello, Ada!"));
+
+        let err = templates.render_template("greet", &json!({"title": "Welcome"})).unwrap_err();
+        assert!(err.to_string().contains("Missing required template variable 'name'"));
+    }
+
+    #[test]
+    fn test_load_from_dir_missing_dir_returns_builtins_only() {
+        let templates = TaskTemplates::load_from_dir(Path::new("/nonexistent/prime/templates")).unwrap();
+        assert_eq!(templates.list_templates().len(), 3);
+    }
 }
\ No newline at end of file
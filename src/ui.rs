@@ -3,6 +3,8 @@
 
 use crossterm::style::Stylize;
 
+use crate::highlight;
+
 /// Default content width for separators/panels.
 pub const WIDTH: usize = 70;
 
@@ -32,8 +34,9 @@ pub fn err_tag(text: &str) -> String {
     format!("[ERR] {}", text).red().to_string()
 }
 
-/// Compact preview from large text (cap lines and characters).
-pub fn preview(text: &str, max_lines: usize, max_chars: usize) -> String {
+/// Compact preview from large text (cap lines and characters), optionally
+/// syntax-highlighted when `lang` names a known language or file extension.
+pub fn preview(text: &str, max_lines: usize, max_chars: usize, lang: Option<&str>) -> String {
     let mut s = text.trim().to_string();
     if s.len() > max_chars {
         s.truncate(max_chars);
@@ -44,23 +47,24 @@ pub fn preview(text: &str, max_lines: usize, max_chars: usize) -> String {
     if text.lines().count() > max_lines {
         joined.push_str("\n… (output truncated)");
     }
-    joined
+    highlight::highlight(&joined, lang)
 }
 
-/// Simple, clean panel with a title and body.
+/// Simple, clean panel with a title and body, syntax-highlighting the body
+/// when `lang` names a known language or file extension.
 /// Layout:
 /// ─────────────────────────────────────────────
 /// █ TITLE
 /// <body>
 /// ─────────────────────────────────────────────
-pub fn panel(title: &str, body: &str) -> String {
+pub fn panel(title: &str, body: &str, lang: Option<&str>) -> String {
     let mut out = String::new();
     out.push_str(&hr());
     out.push('\n');
     out.push_str(&title_line(title));
     if !body.trim().is_empty() {
         out.push('\n');
-        out.push_str(body);
+        out.push_str(&highlight::highlight(body, lang));
     }
     out.push('\n');
     out.push_str(&hr());
@@ -0,0 +1,213 @@
+//! File-change-triggered task re-execution, modeled on Deno's `--watch`
+//! restart-on-change loop: monitors a project directory for changes and
+//! re-runs a configured command whenever sources change, debouncing bursts
+//! of filesystem events and honoring the same ignored-path patterns
+//! `CommandProcessor` already loads for directory listings.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::commands::CommandProcessor;
+use crate::logging::LOG;
+
+/// The default debounce window a `ToolCall::Watch` registration uses when the
+/// `debounce_ms` argument is omitted, matching `WatchSession`'s `DEBOUNCE`.
+pub const DEFAULT_TOOL_WATCH_DEBOUNCE_MS: u64 = 200;
+
+/// One `watch: <paths> [debounce_ms=N]` registration made through the agent
+/// tool protocol. Unlike `WatchSession` (which re-runs a fixed shell command),
+/// an `ActiveWatch` just accumulates which of its paths changed so
+/// `PrimeSession` can hand the LLM a synthetic tool result on its next turn —
+/// the LLM decides what to do about the change, rather than a hardcoded
+/// re-run command.
+///
+/// `paths` are resolved to absolute form by the caller before `register` runs,
+/// so the watch keeps pointing at the same files even if a later `ChangeDir`
+/// moves `self.working_dir` out from under it.
+pub struct ActiveWatch {
+    pub paths: Vec<PathBuf>,
+    changed: Arc<Mutex<BTreeSet<PathBuf>>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl ActiveWatch {
+    /// Starts watching `paths` (already absolute) and spawns a background
+    /// thread that debounces bursts of events for `debounce_ms` before
+    /// folding them into `changed`, the same coalescing `WatchSession::run`
+    /// does for its own re-run loop.
+    pub fn register(paths: Vec<PathBuf>, debounce_ms: u64) -> Result<Self> {
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })
+        .context("Failed to create file watcher")?;
+        for path in &paths {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .with_context(|| format!("Failed to watch {}", path.display()))?;
+        }
+
+        let changed: Arc<Mutex<BTreeSet<PathBuf>>> = Arc::new(Mutex::new(BTreeSet::new()));
+        let changed_for_thread = Arc::clone(&changed);
+        let debounce = Duration::from_millis(debounce_ms);
+        thread::spawn(move || {
+            loop {
+                let first = match rx.recv() {
+                    Ok(event) => event,
+                    Err(_) => break,
+                };
+                let mut pending = BTreeSet::new();
+                if let Ok(event) = first {
+                    pending.extend(event.paths);
+                }
+
+                let mut deadline = Instant::now() + debounce;
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    match rx.recv_timeout(remaining) {
+                        Ok(Ok(event)) => {
+                            pending.extend(event.paths);
+                            deadline = Instant::now() + debounce;
+                        }
+                        Ok(Err(_)) => {}
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+
+                if !pending.is_empty() {
+                    if let Ok(mut guard) = changed_for_thread.lock() {
+                        guard.extend(pending);
+                    }
+                }
+            }
+        });
+
+        Ok(Self { paths, changed, _watcher: watcher })
+    }
+
+    /// Drains and returns every path observed to change since the last call,
+    /// leaving `changed` empty for the next poll.
+    pub fn drain_changes(&self) -> Vec<PathBuf> {
+        let mut guard = self.changed.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::take(&mut *guard).into_iter().collect()
+    }
+}
+
+/// Coalesce bursts of filesystem events (an editor's swap-file dance, a
+/// build touching many outputs) within this window into a single re-run.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches a directory and re-runs `command` through a `CommandProcessor`
+/// whenever a non-ignored file under it changes. Kills any still-running
+/// prior invocation before starting the next one, so a rapid string of
+/// edits doesn't pile up overlapping runs.
+pub struct WatchSession {
+    watch_root: PathBuf,
+    command: String,
+    ignored_path_patterns: Vec<Pattern>,
+    running: Option<Child>,
+}
+
+impl WatchSession {
+    pub fn new(watch_root: impl Into<PathBuf>, command: impl Into<String>, ignored_path_patterns: Vec<Pattern>) -> Self {
+        Self {
+            watch_root: watch_root.into(),
+            command: command.into(),
+            ignored_path_patterns,
+            running: None,
+        }
+    }
+
+    /// Whether `path` matches one of the ignored-path patterns and shouldn't
+    /// trigger a re-run on its own.
+    fn is_ignored(&self, path: &Path) -> bool {
+        let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        self.ignored_path_patterns.iter().any(|p| p.matches_path(path) || p.matches(&file_name))
+    }
+
+    /// Runs an immediate invocation, then watches `self.watch_root` and
+    /// re-runs on every debounced, non-ignored change. Blocks until the
+    /// watcher's channel disconnects (i.e. `watcher` is dropped), so callers
+    /// should run this on its own thread.
+    pub fn run(&mut self, command_processor: &CommandProcessor) -> Result<()> {
+        let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
+        let mut watcher = notify::recommended_watcher(move |res| {
+            let _ = tx.send(res);
+        })
+        .context("Failed to create file watcher")?;
+        watcher
+            .watch(&self.watch_root, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", self.watch_root.display()))?;
+
+        LOG.info(format!("Watching '{}' for changes (command: {})", self.watch_root.display(), self.command));
+        self.trigger(command_processor);
+
+        loop {
+            let first = match rx.recv() {
+                Ok(event) => event,
+                Err(_) => break,
+            };
+            if !Self::event_is_relevant(&first, self) {
+                continue;
+            }
+
+            // Debounce: keep resetting the deadline while related events
+            // keep arriving, then fire once the window goes quiet.
+            let mut deadline = Instant::now() + DEBOUNCE;
+            loop {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(remaining) {
+                    Ok(event) => {
+                        if Self::event_is_relevant(&event, self) {
+                            deadline = Instant::now() + DEBOUNCE;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                }
+            }
+
+            LOG.info("Change detected, restarting...");
+            self.trigger(command_processor);
+        }
+        Ok(())
+    }
+
+    fn event_is_relevant(event: &notify::Result<Event>, this: &WatchSession) -> bool {
+        match event {
+            Ok(event) => event.paths.iter().any(|p| !this.is_ignored(p)),
+            Err(_) => false,
+        }
+    }
+
+    /// Kills whatever invocation is still running, then spawns a fresh one.
+    fn trigger(&mut self, command_processor: &CommandProcessor) {
+        if let Some(mut child) = self.running.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+
+        match command_processor.spawn_shell(&self.command, &self.watch_root) {
+            Ok(child) => self.running = Some(child),
+            Err(e) => LOG.error(format!("Failed to start watched command: {}", e)),
+        }
+    }
+}
+
+impl Drop for WatchSession {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.running.take() {
+            let _ = child.kill();
+        }
+    }
+}
@@ -1,16 +1,206 @@
 // src/web_ops.rs
 use anyhow::{Context as AnyhowContext, Result, anyhow};
+use base64::Engine;
 use reqwest::Client;
 use futures::StreamExt; // For byte_stream().next()
 use log; // For logging
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::Mutex;
 
 const MAX_RESPONSE_BYTES: u64 = 2_000_000; // 2MB limit for fetched content
-const REQUEST_TIMEOUT_SECONDS: u64 = 30;
+/// How long to wait for the TCP connection + response headers. Short,
+/// because a server that can't even start responding this fast is unlikely
+/// to recover.
+const CONNECT_TIMEOUT_SECONDS: u64 = 10;
+/// How long to wait for the *first* body byte once headers are in. Generous,
+/// since some servers sit on a request for a while before streaming.
+const FIRST_BYTE_TIMEOUT_SECONDS: u64 = 60;
+
+/// Marks an `anyhow::Error` as a connection-reset-class failure that
+/// happened before any response body was consumed, so it's safe to retry the
+/// whole request exactly once with a fresh connection. Unwrapped back to the
+/// inner error before it's ever shown to a caller.
+#[derive(Debug)]
+struct RetryableFetchError(anyhow::Error);
+
+impl std::fmt::Display for RetryableFetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for RetryableFetchError {}
+
+/// True for the class of errors worth a single silent retry: the connection
+/// could not be established, or was reset, aborted, hit a broken pipe, or hit
+/// an unexpected EOF. Timeouts aren't included —
+/// they already get their own generous budget above, so a repeat is unlikely
+/// to help.
+fn is_retryable_connection_error(err: &reqwest::Error) -> bool {
+    if err.is_timeout() {
+        return false;
+    }
+    if err.is_connect() {
+        return true;
+    }
+    let message = err.to_string().to_lowercase();
+    message.contains("connection reset")
+        || message.contains("connection aborted")
+        || message.contains("unexpected eof")
+        || message.contains("broken pipe")
+}
+
+fn is_retryable_marker(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<RetryableFetchError>().is_some()
+}
+
+fn unwrap_marker(err: anyhow::Error) -> anyhow::Error {
+    match err.downcast::<RetryableFetchError>() {
+        Ok(marker) => marker.0,
+        Err(original) => original,
+    }
+}
+
+/// Runs `attempt` once; if it fails with a `RetryableFetchError`, runs it
+/// again exactly once (a brand-new GET — nothing from the failed attempt is
+/// reused, so this is always safe). Strips the retry marker from whatever
+/// error is ultimately returned.
+async fn with_single_retry<T, Fut>(url: &str, attempt: impl Fn() -> Fut) -> Result<T>
+where
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    match attempt().await {
+        Ok(value) => Ok(value),
+        Err(e) if is_retryable_marker(&e) => {
+            log::warn!("Transient connection error fetching {}, retrying once: {}", url, e);
+            attempt().await.map_err(unwrap_marker)
+        }
+        Err(e) => Err(unwrap_marker(e)),
+    }
+}
+
+/// One parsed entry from an `AuthTokens` configuration string: either a
+/// bearer token or a username/password pair, to be rendered into an
+/// `Authorization` header for a matching host.
+enum AuthCredential {
+    Bearer(String),
+    Basic { username: String, password: String },
+}
+
+/// A `DENO_AUTH_TOKENS`-style registry mapping hosts to credentials, so the
+/// fetcher can reach private/authenticated URLs without callers threading
+/// secrets through every request. Parsed once (typically from an env var or
+/// config file) from a `;`-separated string of `token@host` or
+/// `user:pass@host` entries, e.g. `"abc123@example.com;user:pw@internal.org"`.
+/// A request's `Authorization` header is attached only when the target
+/// host matches an entry exactly or as a subdomain of it. Because
+/// `handle_fetch_text_web_op` resolves this lookup fresh for every redirect
+/// hop (see `fetch_once`), a credential injected for one host is never
+/// carried along to a different host a redirect lands on.
+#[derive(Default)]
+pub struct AuthTokens {
+    entries: Vec<(String, AuthCredential)>,
+}
+
+impl AuthTokens {
+    /// Parses `config`. Entries missing an `@host` suffix are logged and
+    /// skipped rather than failing the whole registry.
+    pub fn parse(config: &str) -> Self {
+        let mut entries = Vec::new();
+        for raw in config.split(';') {
+            let raw = raw.trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let Some((credential, host)) = raw.rsplit_once('@') else {
+                log::warn!("Ignoring malformed AuthTokens entry (missing '@host'): {}", raw);
+                continue;
+            };
+            let host = host.trim().to_lowercase();
+            if host.is_empty() {
+                log::warn!("Ignoring malformed AuthTokens entry (empty host): {}", raw);
+                continue;
+            }
+            let credential = match credential.split_once(':') {
+                Some((username, password)) => {
+                    AuthCredential::Basic { username: username.to_string(), password: password.to_string() }
+                }
+                None => AuthCredential::Bearer(credential.to_string()),
+            };
+            entries.push((host, credential));
+        }
+        Self { entries }
+    }
+
+    /// The `Authorization` header value to attach for `host`, if any entry
+    /// matches. When more than one entry matches (e.g. both `example.com`
+    /// and `api.example.com` are configured), the most specific (longest)
+    /// host wins.
+    fn header_for(&self, host: &str) -> Option<String> {
+        let host = host.to_lowercase();
+        self.entries
+            .iter()
+            .filter(|(entry_host, _)| host_matches(&host, entry_host))
+            .max_by_key(|(entry_host, _)| entry_host.len())
+            .map(|(_, credential)| match credential {
+                AuthCredential::Bearer(token) => format!("Bearer {}", token),
+                AuthCredential::Basic { username, password } => {
+                    let encoded =
+                        base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password));
+                    format!("Basic {}", encoded)
+                }
+            })
+    }
+}
+
+/// True when `host` is `entry_host` itself, or a subdomain of it.
+fn host_matches(host: &str, entry_host: &str) -> bool {
+    host == entry_host || host.ends_with(&format!(".{}", entry_host))
+}
+
+/// Extracts the lowercased `host` (or `host:port`, when `url` carries a
+/// non-default port) from `url`, if it parses. The port must be included so
+/// this matches the key an `AuthTokens` entry for a non-default-port host
+/// (e.g. `token@127.0.0.1:8080`) is registered under.
+fn host_of(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?.to_lowercase();
+    Some(match parsed.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    })
+}
+
+/// Max redirect hops `handle_fetch_text_web_op`/`handle_fetch_text_web_op_cached`
+/// will follow before giving up with an error.
+const MAX_REDIRECTS: usize = 10;
+
+/// The outcome of a fetch that may have followed one or more redirects: the
+/// body text, whether it was truncated, and the URL the body actually came
+/// from — which can differ from the URL passed in (link shorteners,
+/// `http://` -> `https://` upgrades, canonicalization), so callers can see
+/// where a link actually landed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchOutcome {
+    pub url: String,
+    pub body: String,
+    pub truncated: bool,
+}
+
+/// One `fetch_once` attempt either lands on a usable response (`Done`) or a
+/// redirect to follow next (`Redirect`); `handle_fetch_text_web_op` loops on
+/// the latter.
+enum FetchStep {
+    Done(FetchedBody),
+    Redirect(String),
+}
 
 pub async fn handle_fetch_text_web_op(
     http_client: &Client,
     url: &str,
-) -> Result<String> {
+    auth_tokens: &AuthTokens,
+) -> Result<FetchOutcome> {
     if url.trim().is_empty() {
         return Err(anyhow!("URL cannot be empty."));
     }
@@ -22,12 +212,68 @@ pub async fn handle_fetch_text_web_op(
 
     log::info!("Fetching text from URL: {}", url);
 
-    let response = http_client
-        .get(url)
-        .timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
-        .send()
-        .await
-        .with_context(|| format!("Failed to send request to URL: {}", url))?;
+    let mut current_url = url.to_string();
+    let mut visited = std::collections::HashSet::new();
+    visited.insert(current_url.clone());
+
+    for _ in 0..=MAX_REDIRECTS {
+        let step = with_single_retry(&current_url, || fetch_once(http_client, &current_url, auth_tokens)).await?;
+        match step {
+            FetchStep::Done(body) => {
+                return Ok(FetchOutcome { url: current_url, body: body.text, truncated: body.truncated });
+            }
+            FetchStep::Redirect(next_url) => {
+                if !visited.insert(next_url.clone()) {
+                    return Err(anyhow!("Redirect loop detected while fetching {} (revisited {})", url, next_url));
+                }
+                log::info!("Following redirect: {} -> {}", current_url, next_url);
+                current_url = next_url;
+            }
+        }
+    }
+
+    Err(anyhow!("Exceeded maximum of {} redirects while fetching {}", MAX_REDIRECTS, url))
+}
+
+/// One attempt at sending the request and reading its body, bounded by
+/// `CONNECT_TIMEOUT_SECONDS`. Returns a `RetryableFetchError` for a
+/// connection-reset-class failure at the send stage or while reading the
+/// first body byte (see `read_text_response`). A 3xx response is returned as
+/// `FetchStep::Redirect` rather than followed here, since the caller needs
+/// to recompute per-host auth for the new URL and re-apply `http_client`'s
+/// own timeout/retry handling to the next hop; the `Authorization` header is
+/// therefore never carried across a redirect by construction, matching or
+/// improving on reqwest's built-in cross-host stripping.
+async fn fetch_once(
+    http_client: &Client,
+    url: &str,
+    auth_tokens: &AuthTokens,
+) -> Result<FetchStep> {
+    let mut request = http_client.get(url).header(reqwest::header::ACCEPT_ENCODING, "gzip, br, deflate");
+    if let Some(host) = host_of(url) {
+        if let Some(auth_value) = auth_tokens.header_for(&host) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth_value);
+        }
+    }
+
+    let send_result = tokio::time::timeout(
+        std::time::Duration::from_secs(CONNECT_TIMEOUT_SECONDS),
+        request.send(),
+    )
+    .await
+    .map_err(|_| anyhow!("Connecting to {} timed out after {}s", url, CONNECT_TIMEOUT_SECONDS))?;
+
+    let response = match send_result {
+        Ok(resp) => resp,
+        Err(e) if is_retryable_connection_error(&e) => {
+            return Err(RetryableFetchError(anyhow::Error::new(e)).into());
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to send request to URL: {}", url)),
+    };
+
+    if response.status().is_redirection() {
+        return resolve_redirect(&response, url).map(FetchStep::Redirect);
+    }
 
     if !response.status().is_success() {
         return Err(anyhow!(
@@ -37,58 +283,405 @@ pub async fn handle_fetch_text_web_op(
         ));
     }
 
-    if let Some(content_type) = response.headers().get(reqwest::header::CONTENT_TYPE) {
-        if let Ok(ct_str) = content_type.to_str() {
-            log::debug!("Content-Type for {}: {}", url, ct_str);
-            if ct_str.starts_with("image/")
-                || ct_str.starts_with("audio/")
-                || ct_str.starts_with("video/")
-                || ct_str.starts_with("application/pdf")
-                || ct_str == "application/octet-stream"
-            {
-                return Err(anyhow!(
-                    "URL points to binary or non-text content ({}). Only text-based content can be fetched.",
-                    ct_str
-                ));
-            }
+    Ok(FetchStep::Done(read_text_response(response, url).await?))
+}
+
+/// Resolves a redirect response's `Location` header (absolute or relative to
+/// `url`) into the next URL to fetch, rejecting anything that isn't `http(s)`.
+fn resolve_redirect(response: &reqwest::Response, url: &str) -> Result<String> {
+    let location = response
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| anyhow!("Redirect response from {} ({}) is missing a Location header", url, response.status()))?;
+
+    let base = reqwest::Url::parse(url).with_context(|| format!("Failed to parse URL: {}", url))?;
+    let resolved = base
+        .join(location)
+        .with_context(|| format!("Invalid redirect Location '{}' from {}", location, url))?;
+
+    if resolved.scheme() != "http" && resolved.scheme() != "https" {
+        return Err(anyhow!(
+            "Redirect from {} points to an unsupported scheme: {}",
+            url,
+            resolved
+        ));
+    }
+
+    Ok(resolved.to_string())
+}
+
+/// Decompresses `raw` per the (lowercased) `Content-Encoding` value, if any.
+/// An unrecognized or absent encoding passes `raw` through unchanged.
+fn decompress_body(content_encoding: Option<&str>, raw: Vec<u8>, url: &str) -> Result<Vec<u8>> {
+    match content_encoding.map(|e| e.trim().to_lowercase()) {
+        Some(ref encoding) if encoding == "gzip" => {
+            let mut decoder = flate2::read::GzDecoder::new(&raw[..]);
+            let mut out = Vec::new();
+            decoder
+                .read_to_end(&mut out)
+                .with_context(|| format!("Failed to gzip-decompress response from {}", url))?;
+            Ok(out)
+        }
+        Some(ref encoding) if encoding == "deflate" => {
+            let mut decoder = flate2::read::DeflateDecoder::new(&raw[..]);
+            let mut out = Vec::new();
+            decoder
+                .read_to_end(&mut out)
+                .with_context(|| format!("Failed to inflate response from {}", url))?;
+            Ok(out)
+        }
+        Some(ref encoding) if encoding == "br" => {
+            let mut decoder = brotli::Decompressor::new(&raw[..], 4096);
+            let mut out = Vec::new();
+            decoder
+                .read_to_end(&mut out)
+                .with_context(|| format!("Failed to brotli-decompress response from {}", url))?;
+            Ok(out)
+        }
+        _ => Ok(raw),
+    }
+}
+
+/// Extracts the `charset=` parameter from a `Content-Type` header value, if
+/// present.
+fn parse_charset(content_type: &str) -> Option<String> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let param = param.trim();
+        param.strip_prefix("charset=").map(|c| c.trim_matches('"').to_string())
+    })
+}
+
+/// Decodes `bytes` using the charset declared in `content_type`, when one is
+/// declared and recognized, via `encoding_rs`. Falls back to lossy UTF-8
+/// otherwise, so an undeclared or unrecognized charset never hard-fails the
+/// fetch.
+fn decode_charset(bytes: &[u8], content_type: Option<&str>) -> String {
+    if let Some(label) = content_type.and_then(parse_charset) {
+        if let Some(encoding) = encoding_rs::Encoding::for_label(label.as_bytes()) {
+            let (decoded, _, _had_errors) = encoding.decode(bytes);
+            return decoded.into_owned();
+        }
+    }
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// A successfully read response body, plus everything a caller might need
+/// to cache it or report on it: the revalidation headers for a future
+/// conditional request, and whether the text was cut short by
+/// `MAX_RESPONSE_BYTES`.
+struct FetchedBody {
+    text: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    truncated: bool,
+}
+
+/// Reads a successful response's body into text: rejects binary content
+/// types, reads the (possibly compressed) body fully, transparently
+/// decompresses per `Content-Encoding`, truncates the *decoded* bytes to
+/// `MAX_RESPONSE_BYTES`, then decodes per the `Content-Type` charset (lossy
+/// UTF-8 if none is declared). Also surfaces the `ETag`/`Last-Modified`
+/// validators so callers like `handle_fetch_text_web_op_cached` can store
+/// them for a future conditional request.
+async fn read_text_response(response: reqwest::Response, url: &str) -> Result<FetchedBody> {
+    let etag = response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let last_modified = response
+        .headers()
+        .get(reqwest::header::LAST_MODIFIED)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let content_encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    if let Some(ct_str) = &content_type {
+        log::debug!("Content-Type for {}: {}", url, ct_str);
+        if ct_str.starts_with("image/")
+            || ct_str.starts_with("audio/")
+            || ct_str.starts_with("video/")
+            || ct_str.starts_with("application/pdf")
+            || ct_str == "application/octet-stream"
+        {
+            return Err(anyhow!(
+                "URL points to binary or non-text content ({}). Only text-based content can be fetched.",
+                ct_str
+            ));
         }
     }
 
     let mut byte_stream = response.bytes_stream();
-    let mut buffer = Vec::new();
-    let mut total_bytes_read: u64 = 0;
-    let mut truncated = false;
+    let mut raw_buffer = Vec::new();
+    let mut is_first_chunk = true;
 
-    while let Some(chunk_result) = byte_stream.next().await {
-        let chunk = chunk_result.with_context(|| format!("Error reading stream from URL: {}", url))?;
-        if total_bytes_read.saturating_add(chunk.len() as u64) > MAX_RESPONSE_BYTES {
-            let remaining_space = MAX_RESPONSE_BYTES.saturating_sub(total_bytes_read);
-            if remaining_space > 0 {
-               buffer.extend_from_slice(&chunk[..(remaining_space as usize)]);
-               total_bytes_read = total_bytes_read.saturating_add(remaining_space);
+    loop {
+        let next = if is_first_chunk {
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(FIRST_BYTE_TIMEOUT_SECONDS),
+                byte_stream.next(),
+            )
+            .await
+            {
+                Ok(next) => next,
+                Err(_) => {
+                    return Err(anyhow!(
+                        "Timed out waiting for the first byte from {} after {}s",
+                        url,
+                        FIRST_BYTE_TIMEOUT_SECONDS
+                    ));
+                }
             }
-            truncated = true;
-            log::warn!("Response from {} truncated at {} bytes.", url, MAX_RESPONSE_BYTES);
-            break;
-        }
-        buffer.extend_from_slice(&chunk);
-        total_bytes_read = total_bytes_read.saturating_add(chunk.len() as u64);
+        } else {
+            byte_stream.next().await
+        };
+
+        let Some(chunk_result) = next else { break };
+        let chunk = match chunk_result {
+            Ok(chunk) => chunk,
+            Err(e) if is_first_chunk && is_retryable_connection_error(&e) => {
+                return Err(RetryableFetchError(anyhow::Error::new(e).context(format!(
+                    "Connection error reading the first byte from {}",
+                    url
+                )))
+                .into());
+            }
+            Err(e) => return Err(e).with_context(|| format!("Error reading stream from URL: {}", url)),
+        };
+        raw_buffer.extend_from_slice(&chunk);
+        is_first_chunk = false;
     }
 
-    if buffer.is_empty() && total_bytes_read == 0 && !truncated {
+    let decoded_bytes = decompress_body(content_encoding.as_deref(), raw_buffer, url)?;
+
+    let mut truncated = false;
+    let content_bytes = if decoded_bytes.len() as u64 > MAX_RESPONSE_BYTES {
+        truncated = true;
+        log::warn!("Response from {} truncated at {} decoded bytes.", url, MAX_RESPONSE_BYTES);
+        &decoded_bytes[..(MAX_RESPONSE_BYTES as usize)]
+    } else {
+        &decoded_bytes[..]
+    };
+
+    if content_bytes.is_empty() && !truncated {
         log::info!("Response from {} was successful but empty.", url);
         // Return empty string for successful empty responses.
     }
 
-    let mut text_content = String::from_utf8(buffer)
-        .with_context(|| format!("Failed to decode response from {} as UTF-8. Content might be non-text or use an unsupported encoding.", url))?;
+    let mut text_content = decode_charset(content_bytes, content_type.as_deref());
 
     if truncated {
         text_content.push_str("\n\n... (content truncated due to size limit)");
     }
 
-    log::info!("Successfully fetched and processed content from {}. Size: {} bytes. Truncated: {}", url, total_bytes_read, truncated);
-    Ok(text_content)
+    log::info!(
+        "Successfully fetched and processed content from {}. Size: {} bytes. Truncated: {}",
+        url,
+        content_bytes.len(),
+        truncated
+    );
+    Ok(FetchedBody { text: text_content, etag, last_modified, truncated })
+}
+
+/// How `handle_fetch_text_web_op_cached` should treat a URL's `HttpCache` entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSetting {
+    /// Send a conditional request (`If-None-Match`/`If-Modified-Since`) when
+    /// a cached entry exists, and reuse the cached body on `304 Not Modified`.
+    Use,
+    /// Ignore any cached entry and always perform a full, unconditional fetch.
+    ReloadAll,
+    /// Never touch the network; return the cached body, or error if there
+    /// isn't one.
+    Only,
+}
+
+/// A cached response body plus the validators needed to revalidate it.
+struct CachedResponse {
+    body: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// A per-URL cache of fetched bodies and their `ETag`/`Last-Modified`
+/// validators, so `handle_fetch_text_web_op_cached` can send a conditional
+/// request on a repeat fetch and reuse the cached body when the server
+/// replies `304 Not Modified` instead of re-downloading unchanged content.
+#[derive(Default)]
+pub struct HttpCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl HttpCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, url: &str) -> Option<(Option<String>, Option<String>, String)> {
+        self.entries
+            .lock()
+            .unwrap()
+            .get(url)
+            .map(|entry| (entry.etag.clone(), entry.last_modified.clone(), entry.body.clone()))
+    }
+
+    fn store(&self, url: &str, body: String, etag: Option<String>, last_modified: Option<String>) {
+        if etag.is_none() && last_modified.is_none() {
+            return;
+        }
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), CachedResponse { body, etag, last_modified });
+    }
+}
+
+/// Like `handle_fetch_text_web_op`, but consults `cache` first: under
+/// `CacheSetting::Use` (the common case) it sends `If-None-Match`/
+/// `If-Modified-Since` from a prior cached entry and reuses the cached body
+/// on `304 Not Modified`; `ReloadAll` always re-fetches; `Only` never hits
+/// the network at all.
+pub async fn handle_fetch_text_web_op_cached(
+    http_client: &Client,
+    url: &str,
+    cache: &HttpCache,
+    setting: CacheSetting,
+    auth_tokens: &AuthTokens,
+) -> Result<FetchOutcome> {
+    if url.trim().is_empty() {
+        return Err(anyhow!("URL cannot be empty."));
+    }
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err(anyhow!("Invalid URL scheme: URL must start with http:// or https://"));
+    }
+
+    let cached = cache.get(url);
+
+    if setting == CacheSetting::Only {
+        return cached
+            .map(|(_, _, body)| FetchOutcome { url: url.to_string(), body, truncated: false })
+            .ok_or_else(|| anyhow!("No cached response for {} and cache setting is 'Only'.", url));
+    }
+
+    log::info!("Fetching text from URL (cache: {:?}): {}", setting, url);
+
+    let mut current_url = url.to_string();
+    let mut visited = std::collections::HashSet::new();
+    visited.insert(current_url.clone());
+
+    for _ in 0..=MAX_REDIRECTS {
+        // Conditional revalidation headers only make sense on the exact
+        // cached URL; once a redirect moves us elsewhere, the original
+        // entry's ETag/Last-Modified don't describe that new resource.
+        let conditional = if setting == CacheSetting::Use && current_url == url { cached.as_ref() } else { None };
+
+        let step = with_single_retry(&current_url, || {
+            fetch_once_cached(http_client, &current_url, conditional, auth_tokens)
+        })
+        .await?;
+
+        match step {
+            CachedFetchStep::NotModified => {
+                log::info!("Response from {} was 304 Not Modified; serving cached body.", current_url);
+                return cached
+                    .map(|(_, _, body)| FetchOutcome { url: current_url.clone(), body, truncated: false })
+                    .ok_or_else(|| {
+                        anyhow!("Server returned 304 Not Modified for {} but no cached response exists.", current_url)
+                    });
+            }
+            CachedFetchStep::Done(body) => {
+                cache.store(url, body.text.clone(), body.etag, body.last_modified);
+                return Ok(FetchOutcome { url: current_url, body: body.text, truncated: body.truncated });
+            }
+            CachedFetchStep::Redirect(next_url) => {
+                if !visited.insert(next_url.clone()) {
+                    return Err(anyhow!("Redirect loop detected while fetching {} (revisited {})", url, next_url));
+                }
+                log::info!("Following redirect: {} -> {}", current_url, next_url);
+                current_url = next_url;
+            }
+        }
+    }
+
+    Err(anyhow!("Exceeded maximum of {} redirects while fetching {}", MAX_REDIRECTS, url))
+}
+
+/// One `fetch_once_cached` attempt: a `304 Not Modified`, a fresh body, or a
+/// redirect to follow next.
+enum CachedFetchStep {
+    NotModified,
+    Done(FetchedBody),
+    Redirect(String),
+}
+
+/// One attempt at the conditional request + body read, mirroring
+/// `fetch_once`'s timeout, retry-marker, and redirect behavior. `cached` is
+/// only `Some` when conditional headers should be sent for this hop.
+async fn fetch_once_cached(
+    http_client: &Client,
+    url: &str,
+    cached: Option<&(Option<String>, Option<String>, String)>,
+    auth_tokens: &AuthTokens,
+) -> Result<CachedFetchStep> {
+    let mut request = http_client
+        .get(url)
+        .header(reqwest::header::ACCEPT_ENCODING, "gzip, br, deflate");
+
+    if let Some(host) = host_of(url) {
+        if let Some(auth_value) = auth_tokens.header_for(&host) {
+            request = request.header(reqwest::header::AUTHORIZATION, auth_value);
+        }
+    }
+
+    if let Some((etag, last_modified, _)) = cached {
+        if let Some(etag) = etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified.clone());
+        }
+    }
+
+    let send_result =
+        tokio::time::timeout(std::time::Duration::from_secs(CONNECT_TIMEOUT_SECONDS), request.send())
+            .await
+            .map_err(|_| anyhow!("Connecting to {} timed out after {}s", url, CONNECT_TIMEOUT_SECONDS))?;
+
+    let response = match send_result {
+        Ok(resp) => resp,
+        Err(e) if is_retryable_connection_error(&e) => {
+            return Err(RetryableFetchError(anyhow::Error::new(e)).into());
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to send request to URL: {}", url)),
+    };
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(CachedFetchStep::NotModified);
+    }
+
+    if response.status().is_redirection() {
+        return resolve_redirect(&response, url).map(CachedFetchStep::Redirect);
+    }
+
+    if !response.status().is_success() {
+        return Err(anyhow!(
+            "Request to {} failed with status: {}",
+            url,
+            response.status()
+        ));
+    }
+
+    Ok(CachedFetchStep::Done(read_text_response(response, url).await?))
 }
 
 #[cfg(test)]
@@ -98,8 +691,9 @@ mod tests {
     use reqwest::Client; // To create a client for tests
 
     fn create_test_client() -> Client {
-        // Create a basic client for tests.
-        Client::builder().build().unwrap()
+        // Redirects are followed manually by `handle_fetch_text_web_op`, so the
+        // underlying client must not also try to follow them.
+        Client::builder().redirect(reqwest::redirect::Policy::none()).build().unwrap()
     }
 
     #[tokio::test]
@@ -116,9 +710,11 @@ mod tests {
                 .body(expected_body);
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), expected_body);
+        let outcome = result.unwrap();
+        assert_eq!(outcome.body, expected_body);
+        assert_eq!(outcome.url, mock_url);
     }
 
     #[tokio::test]
@@ -132,7 +728,7 @@ mod tests {
             then.status(404);
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("failed with status: 404 Not Found"));
     }
@@ -148,7 +744,7 @@ mod tests {
             then.status(500);
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("failed with status: 500 Internal Server Error"));
     }
@@ -158,7 +754,7 @@ mod tests {
         let client = create_test_client();
         let non_existent_url = "http://localhost:12345/nonexistent";
 
-        let result = handle_fetch_text_web_op(&client, non_existent_url).await;
+        let result = handle_fetch_text_web_op(&client, non_existent_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         // Check that the error message indicates a failure to send the request or a connection problem
         let err_string = result.err().unwrap().to_string().to_lowercase();
@@ -174,10 +770,10 @@ mod tests {
         server.mock(|when, then| {
             when.method(GET).path("/timeout");
             then.status(200)
-                .delay(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECONDS + 1)); // Delay longer than handler's timeout
+                .delay(std::time::Duration::from_secs(CONNECT_TIMEOUT_SECONDS + 1)); // Delay longer than the connect timeout
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         let err_string = result.err().unwrap().to_string().to_lowercase();
         assert!(err_string.contains("timed out") || err_string.contains("timeout"));
@@ -199,9 +795,11 @@ mod tests {
                 .body(&body_content);
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_ok());
-        let fetched_text = result.unwrap();
+        let outcome = result.unwrap();
+        assert!(outcome.truncated);
+        let fetched_text = outcome.body;
         // Expected length is MAX_RESPONSE_BYTES + length of truncation message
         let expected_max_len = (MAX_RESPONSE_BYTES as usize) + "\n\n... (content truncated due to size limit)".len();
         assert!(fetched_text.len() <= expected_max_len);
@@ -221,7 +819,7 @@ mod tests {
             then.status(200).header("Content-Type", "image/jpeg");
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("points to binary or non-text content (image/jpeg)"));
     }
@@ -237,7 +835,7 @@ mod tests {
             then.status(200).header("Content-Type", "application/pdf");
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("points to binary or non-text content (application/pdf)"));
     }
@@ -245,7 +843,7 @@ mod tests {
     #[tokio::test]
     async fn test_fetch_empty_url_error() {
         let client = create_test_client();
-        let result = handle_fetch_text_web_op(&client, "").await;
+        let result = handle_fetch_text_web_op(&client, "", &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("URL cannot be empty"));
     }
@@ -253,11 +851,100 @@ mod tests {
     #[tokio::test]
     async fn test_fetch_invalid_scheme_error() {
         let client = create_test_client();
-        let result = handle_fetch_text_web_op(&client, "ftp://example.com").await;
+        let result = handle_fetch_text_web_op(&client, "ftp://example.com", &AuthTokens::default()).await;
         assert!(result.is_err());
         assert!(result.err().unwrap().to_string().contains("Invalid URL scheme"));
     }
 
+    #[tokio::test]
+    async fn test_fetch_decompresses_gzip_body() {
+        use std::io::Write;
+
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/gzip");
+
+        let plain = "Hello, gzip world!";
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(plain.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/gzip");
+            then.status(200)
+                .header("Content-Type", "text/plain")
+                .header("Content-Encoding", "gzip")
+                .body(gzipped);
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
+        assert_eq!(result.unwrap().body, plain);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decodes_declared_charset() {
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/latin1");
+
+        // "café" in ISO-8859-1: the trailing 'é' is a single 0xE9 byte.
+        let latin1_body: Vec<u8> = vec![b'c', b'a', b'f', 0xE9];
+
+        server.mock(|when, then| {
+            when.method(GET).path("/latin1");
+            then.status(200)
+                .header("Content-Type", "text/plain; charset=iso-8859-1")
+                .body(latin1_body);
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
+        assert_eq!(result.unwrap().body, "café");
+    }
+
+    #[tokio::test]
+    async fn test_cached_fetch_reuses_body_on_304() {
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/cached");
+        let cache = HttpCache::new();
+
+        server.mock(|when, then| {
+            when.method(GET).path("/cached");
+            then.status(200)
+                .header("Content-Type", "text/plain")
+                .header("ETag", "\"abc123\"")
+                .body("fresh content");
+        });
+
+        let first = handle_fetch_text_web_op_cached(&client, &mock_url, &cache, CacheSetting::Use, &AuthTokens::default())
+            .await
+            .unwrap();
+        assert_eq!(first.body, "fresh content");
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/cached")
+                .header("If-None-Match", "\"abc123\"");
+            then.status(304);
+        });
+
+        let second = handle_fetch_text_web_op_cached(&client, &mock_url, &cache, CacheSetting::Use, &AuthTokens::default())
+            .await
+            .unwrap();
+        assert_eq!(second.body, "fresh content");
+    }
+
+    #[tokio::test]
+    async fn test_cache_only_without_prior_entry_errors() {
+        let client = create_test_client();
+        let cache = HttpCache::new();
+        let result =
+            handle_fetch_text_web_op_cached(&client, "http://localhost:1/never-cached", &cache, CacheSetting::Only, &AuthTokens::default())
+                .await;
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("cache setting is 'Only'"));
+    }
+
     #[tokio::test]
     async fn test_fetch_successful_empty_response() {
         let server = MockServer::start();
@@ -271,8 +958,116 @@ mod tests {
                 .body(""); // Empty body
         });
 
-        let result = handle_fetch_text_web_op(&client, &mock_url).await;
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "");
+        assert_eq!(result.unwrap().body, "");
+    }
+
+    #[test]
+    fn test_auth_tokens_matches_subdomains_and_prefers_most_specific() {
+        let auth = AuthTokens::parse("root-token@example.com;sub-token@api.example.com");
+        assert_eq!(auth.header_for("example.com"), Some("Bearer root-token".to_string()));
+        assert_eq!(auth.header_for("www.example.com"), Some("Bearer root-token".to_string()));
+        assert_eq!(auth.header_for("api.example.com"), Some("Bearer sub-token".to_string()));
+        assert_eq!(auth.header_for("other.org"), None);
+    }
+
+    #[test]
+    fn test_auth_tokens_parses_basic_credentials() {
+        let auth = AuthTokens::parse("user:s3cret@internal.example.org");
+        let expected = format!("Basic {}", base64::engine::general_purpose::STANDARD.encode("user:s3cret"));
+        assert_eq!(auth.header_for("internal.example.org"), Some(expected));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_attaches_authorization_header_for_matching_host() {
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/protected");
+        let host = format!("127.0.0.1:{}", server.port());
+        let auth = AuthTokens::parse(&format!("s3cr3t-token@{}", host));
+
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/protected")
+                .header("Authorization", "Bearer s3cr3t-token");
+            then.status(200).header("Content-Type", "text/plain").body("authorized");
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &auth).await;
+        assert_eq!(result.unwrap().body, "authorized");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_follows_relative_redirect_and_reports_final_url() {
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/start");
+
+        server.mock(|when, then| {
+            when.method(GET).path("/start");
+            then.status(302).header("Location", "/end");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/end");
+            then.status(200).header("Content-Type", "text/plain").body("landed");
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
+        let outcome = result.unwrap();
+        assert_eq!(outcome.body, "landed");
+        assert_eq!(outcome.url, server.url("/end"));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_detects_redirect_loop() {
+        let server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = server.url("/loop-a");
+
+        server.mock(|when, then| {
+            when.method(GET).path("/loop-a");
+            then.status(302).header("Location", "/loop-b");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/loop-b");
+            then.status(302).header("Location", "/loop-a");
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &AuthTokens::default()).await;
+        assert!(result.is_err());
+        assert!(result.err().unwrap().to_string().contains("Redirect loop detected"));
+    }
+
+    // Both mock servers bind to `127.0.0.1` on distinct, non-default ports,
+    // so `origin_host`/`other_server`'s URL only disagree by port. `host_of`
+    // must fold that port into its lookup key, or the "cross-origin" distinction
+    // this test relies on collapses and the scenario it's meant to cover
+    // (the AuthTokens entry not matching `other_server`) never occurs.
+    #[tokio::test]
+    async fn test_fetch_drops_authorization_header_on_cross_origin_redirect() {
+        let origin_server = MockServer::start();
+        let other_server = MockServer::start();
+        let client = create_test_client();
+        let mock_url = origin_server.url("/start");
+
+        let origin_host = format!("127.0.0.1:{}", origin_server.port());
+        let auth = AuthTokens::parse(&format!("origin-only-token@{}", origin_host));
+
+        origin_server.mock(|when, then| {
+            when.method(GET)
+                .path("/start")
+                .header("Authorization", "Bearer origin-only-token");
+            then.status(302).header("Location", other_server.url("/elsewhere"));
+        });
+        // No Authorization header expected here: the redirect crosses to a
+        // host the AuthTokens entry doesn't match.
+        other_server.mock(|when, then| {
+            when.method(GET).path("/elsewhere").header_missing("Authorization");
+            then.status(200).header("Content-Type", "text/plain").body("elsewhere");
+        });
+
+        let result = handle_fetch_text_web_op(&client, &mock_url, &auth).await;
+        assert_eq!(result.unwrap().body, "elsewhere");
     }
 }